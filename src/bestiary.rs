@@ -0,0 +1,69 @@
+use std::collections::BTreeMap;
+
+/// What player knows about one kind of enemies.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Record {
+    pub encountered: u32,
+    pub killed: u32,
+}
+
+/// Enemies player has met, by kind.
+#[derive(Debug, Default)]
+pub struct Bestiary {
+    records: BTreeMap<String, Record>,
+}
+
+impl Bestiary {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn encountered(&mut self, kind: &str) {
+        self.records
+            .entry(kind.to_string())
+            .or_default()
+            .encountered += 1;
+    }
+
+    pub fn killed(&mut self, kind: &str) {
+        self.records.entry(kind.to_string()).or_default().killed += 1;
+    }
+
+    /// Record of enemy kind, empty one if it was never met.
+    pub fn record(&self, kind: &str) -> Record {
+        self.records.get(kind).copied().unwrap_or_default()
+    }
+
+    /// Records of all met enemy kinds in alphabetical order.
+    pub fn records(&self) -> impl Iterator<Item = (&str, Record)> {
+        self.records
+            .iter()
+            .map(|(kind, record)| (kind.as_str(), *record))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn enemies_are_counted() {
+        let mut bestiary = Bestiary::new();
+        bestiary.encountered("rat");
+        bestiary.encountered("goblin");
+        bestiary.encountered("rat");
+        bestiary.killed("rat");
+
+        let records: Vec<(&str, Record)> = bestiary.records().collect();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].0, "goblin");
+        assert_eq!(
+            bestiary.record("rat"),
+            Record {
+                encountered: 2,
+                killed: 1
+            }
+        );
+        assert_eq!(bestiary.record("bandit"), Record::default());
+    }
+}
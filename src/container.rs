@@ -0,0 +1,128 @@
+use crate::{item::Item, loot};
+use rand::Rng;
+
+const LOCKED: &str = "locked";
+const BASE_LOCKPICK_CHANCE: f64 = 0.2;
+const LOCKPICK_CHANCE_PER_DEXTERITY: f64 = 0.08;
+const MAX_LOCKPICK_CHANCE: f64 = 0.95;
+
+/// Items lying in chest, barrel or other container placed on map.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Container {
+    items: Vec<Item>,
+    locked: bool,
+}
+
+impl Container {
+    pub fn new(items: Vec<Item>, locked: bool) -> Self {
+        Self { items, locked }
+    }
+
+    /// Parses container contents in format: `[locked] item, item, ...`. Items are written
+    /// as item codes or as `@table`, which is replaced with items rolled from loot table.
+    pub fn parse(text: &str, rng: &mut impl Rng) -> Result<Self, String> {
+        let text = text.trim();
+        let (locked, text) = match text.strip_prefix(LOCKED) {
+            Some(rest) => (true, rest),
+            None => (false, text),
+        };
+
+        let mut items = Vec::new();
+        for code in text.split(',').map(|code| code.trim()) {
+            if code.is_empty() {
+                continue;
+            }
+
+            match code.strip_prefix('@') {
+                Some(name) => {
+                    let table = loot::tables()
+                        .get(name)
+                        .ok_or(format!("unknown loot table '{}'", name))?;
+                    items.extend(table.roll(rng).items);
+                }
+                None => {
+                    items.push(Item::from_code(code).ok_or(format!("unknown item '{}'", code))?)
+                }
+            }
+        }
+
+        Ok(Self::new(items, locked))
+    }
+
+    /// Text form of container, which can be read back with `parse`.
+    pub fn to_code(&self) -> String {
+        let items: Vec<String> = self.items.iter().map(|item| item.to_code()).collect();
+        let items = items.join(", ");
+
+        match (self.locked, items.is_empty()) {
+            (true, true) => LOCKED.to_string(),
+            (true, false) => format!("{} {}", LOCKED, items),
+            (false, _) => items,
+        }
+    }
+
+    pub fn items(&self) -> &[Item] {
+        &self.items
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.locked
+    }
+
+    pub fn unlock(&mut self) {
+        self.locked = false;
+    }
+
+    pub fn add_items(&mut self, items: Vec<Item>) {
+        self.items.extend(items);
+    }
+
+    pub fn take(&mut self, index: usize) -> Option<Item> {
+        if index < self.items.len() {
+            Some(self.items.remove(index))
+        } else {
+            None
+        }
+    }
+
+    pub fn take_all(&mut self) -> Vec<Item> {
+        self.items.drain(..).collect()
+    }
+}
+
+/// Chance of opening lock with lockpick.
+pub fn lockpick_chance(dexterity: u8) -> f64 {
+    (BASE_LOCKPICK_CHANCE + dexterity as f64 * LOCKPICK_CHANCE_PER_DEXTERITY)
+        .min(MAX_LOCKPICK_CHANCE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn container_codes() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let container = Container::parse("locked key, lockpick", &mut rng).unwrap();
+
+        assert!(container.is_locked());
+        assert_eq!(container.items(), &[Item::key(), Item::lockpick()]);
+        assert_eq!(container.to_code(), "locked key, lockpick");
+        assert_eq!(
+            Container::parse("", &mut rng),
+            Ok(Container::new(vec![], false))
+        );
+        assert!(Container::parse("@no_such_table", &mut rng).is_err());
+    }
+
+    #[test]
+    fn take_items() {
+        let mut container = Container::new(vec![Item::key(), Item::lockpick()], false);
+
+        assert_eq!(container.take(1), Some(Item::lockpick()));
+        assert_eq!(container.take(1), None);
+        assert_eq!(container.take_all(), vec![Item::key()]);
+        assert!(container.items().is_empty());
+    }
+}
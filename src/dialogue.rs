@@ -0,0 +1,473 @@
+use crate::{
+    flags::{FlagValue, Flags},
+    item::Item,
+    player::Player,
+    quest::Journal,
+};
+use core::fmt;
+use std::{
+    collections::HashMap,
+    convert::TryFrom,
+    fs,
+    io::{self, ErrorKind},
+    sync::OnceLock,
+};
+
+pub const DIALOGUES_FILE: &str = "data/dialogues.def";
+
+/// Every dialogue begins with node of this name.
+pub const START_NODE: &str = "start";
+
+static DIALOGUES: OnceLock<Dialogues> = OnceLock::new();
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Stat {
+    Strength,
+    Dexterity,
+    Intelligence,
+}
+
+/// Requirement which has to be met to show dialogue choice.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Condition {
+    /// Flag is set, or has given value.
+    Flag(String, Option<FlagValue>),
+    NotFlag(String),
+    Item(Item),
+    Gold(u32),
+    Stat(Stat, u8),
+    QuestActive(String),
+    QuestDone(String),
+    /// Quest was never taken.
+    QuestNew(String),
+}
+
+impl Condition {
+    pub fn is_met(&self, player: &Player, flags: &Flags, journal: &Journal) -> bool {
+        match self {
+            Condition::Flag(name, None) => flags.is_set(name),
+            Condition::Flag(name, Some(value)) => flags.get(name) == Some(value),
+            Condition::NotFlag(name) => !flags.is_set(name),
+            Condition::Item(item) => player.inventory().contains(item),
+            Condition::Gold(gold) => player.gold() >= *gold,
+            Condition::Stat(stat, value) => {
+                let current = match stat {
+                    Stat::Strength => player.strenght(),
+                    Stat::Dexterity => player.dexterity(),
+                    Stat::Intelligence => player.intelligence(),
+                };
+                current >= *value
+            }
+            Condition::QuestActive(id) => journal.is_active(id),
+            Condition::QuestDone(id) => journal.is_completed(id),
+            Condition::QuestNew(id) => !journal.is_active(id) && !journal.is_completed(id),
+        }
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let (name, value) = text.split_once(' ').unwrap_or((text, ""));
+        let value = value.trim();
+        let number = || {
+            value
+                .parse()
+                .map_err(|_| format!("invalid number '{}'", value))
+        };
+        let stat =
+            || u8::try_from(number()?).map_err(|_| format!("stat value {} is too big", value));
+
+        match name {
+            "flag" => {
+                let (name, value) = parse_flag(value)?;
+                Ok(Condition::Flag(name, value))
+            }
+            "not_flag" => Ok(Condition::NotFlag(value.to_string())),
+            "item" => parse_item(value).map(Condition::Item),
+            "gold" => Ok(Condition::Gold(number()?)),
+            "strength" => Ok(Condition::Stat(Stat::Strength, stat()?)),
+            "dexterity" => Ok(Condition::Stat(Stat::Dexterity, stat()?)),
+            "intelligence" => Ok(Condition::Stat(Stat::Intelligence, stat()?)),
+            "quest_active" => Ok(Condition::QuestActive(value.to_string())),
+            "quest_done" => Ok(Condition::QuestDone(value.to_string())),
+            "quest_new" => Ok(Condition::QuestNew(value.to_string())),
+            _ => Err(format!("unknown condition '{}'", name)),
+        }
+    }
+}
+
+/// What happens after player picks dialogue choice.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Effect {
+    GiveItem(Item),
+    TakeItem(Item),
+    GiveGold(u32),
+    TakeGold(u32),
+    SetFlag(String, FlagValue),
+    /// Adds number to integer flag.
+    AddFlag(String, i64),
+    ClearFlag(String),
+    StartQuest(String),
+    /// Lets player cross deep water.
+    LearnSwimming,
+    /// Gives player boat, which also carries over deep water.
+    GiveBoat,
+    /// Ends dialogue with a fight against random enemy. NPC itself doesn't fight and stays
+    /// on map.
+    Fight,
+    /// Ends dialogue and opens trade with merchant standing at NPC position.
+    Trade,
+}
+
+impl Effect {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let (name, value) = text.split_once(' ').unwrap_or((text, ""));
+        let value = value.trim();
+        let number = || {
+            value
+                .parse()
+                .map_err(|_| format!("invalid number '{}'", value))
+        };
+
+        match name {
+            "give_item" => parse_item(value).map(Effect::GiveItem),
+            "take_item" => parse_item(value).map(Effect::TakeItem),
+            "give_gold" => Ok(Effect::GiveGold(number()?)),
+            "take_gold" => Ok(Effect::TakeGold(number()?)),
+            "set_flag" => {
+                let (name, value) = parse_flag(value)?;
+                Ok(Effect::SetFlag(
+                    name,
+                    value.unwrap_or(FlagValue::Bool(true)),
+                ))
+            }
+            "add_flag" => match value.split_once(' ') {
+                Some((name, amount)) => match amount.trim().parse() {
+                    Ok(amount) => Ok(Effect::AddFlag(name.to_string(), amount)),
+                    Err(_) => Err(format!("invalid number '{}'", amount.trim())),
+                },
+                None => Err("expected flag name and number".to_string()),
+            },
+            "clear_flag" => Ok(Effect::ClearFlag(value.to_string())),
+            "start_quest" => Ok(Effect::StartQuest(value.to_string())),
+            "learn_swimming" => Ok(Effect::LearnSwimming),
+            "give_boat" => Ok(Effect::GiveBoat),
+            "fight" => Ok(Effect::Fight),
+            "trade" => Ok(Effect::Trade),
+            _ => Err(format!("unknown effect '{}'", name)),
+        }
+    }
+}
+
+impl fmt::Display for Effect {
+    /// Writes effect in the same format, which is read by `parse`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Effect::GiveItem(item) => write!(f, "give_item {}", item.to_code()),
+            Effect::TakeItem(item) => write!(f, "take_item {}", item.to_code()),
+            Effect::GiveGold(gold) => write!(f, "give_gold {}", gold),
+            Effect::TakeGold(gold) => write!(f, "take_gold {}", gold),
+            Effect::SetFlag(name, value) => write!(f, "set_flag {} {}", name, value.to_code()),
+            Effect::AddFlag(name, amount) => write!(f, "add_flag {} {}", name, amount),
+            Effect::ClearFlag(name) => write!(f, "clear_flag {}", name),
+            Effect::StartQuest(id) => write!(f, "start_quest {}", id),
+            Effect::LearnSwimming => write!(f, "learn_swimming"),
+            Effect::GiveBoat => write!(f, "give_boat"),
+            Effect::Fight => write!(f, "fight"),
+            Effect::Trade => write!(f, "trade"),
+        }
+    }
+}
+
+/// Parses flag name with optional value, e.g. `gate_guard "Tom"`.
+fn parse_flag(text: &str) -> Result<(String, Option<FlagValue>), String> {
+    match text.split_once(' ') {
+        Some((name, value)) => Ok((name.to_string(), Some(FlagValue::parse(value)))),
+        None if !text.is_empty() => Ok((text.to_string(), None)),
+        None => Err("missing flag name".to_string()),
+    }
+}
+
+fn parse_item(code: &str) -> Result<Item, String> {
+    Item::from_code(code).ok_or(format!("unknown item '{}'", code))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Choice {
+    pub text: String,
+    /// Node shown after this choice. Dialogue ends when it is missing.
+    pub next: Option<String>,
+    pub conditions: Vec<Condition>,
+    pub effects: Vec<Effect>,
+}
+
+impl Choice {
+    pub fn is_available(&self, player: &Player, flags: &Flags, journal: &Journal) -> bool {
+        self.conditions
+            .iter()
+            .all(|condition| condition.is_met(player, flags, journal))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Node {
+    /// Paragraphs said by NPC.
+    pub text: Vec<String>,
+    pub choices: Vec<Choice>,
+}
+
+pub struct Dialogue {
+    nodes: HashMap<String, Node>,
+}
+
+impl Dialogue {
+    pub fn node(&self, name: &str) -> Option<&Node> {
+        self.nodes.get(name)
+    }
+}
+
+/// NPC standing on map, who talks with player.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Npc {
+    pub name: String,
+    pub dialogue: String,
+}
+
+impl Npc {
+    /// Parses NPC in format: `@dialogue name`.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let (dialogue, name) = text
+            .trim()
+            .split_once(' ')
+            .ok_or("expected @dialogue and name")?;
+        let dialogue = dialogue
+            .strip_prefix('@')
+            .ok_or(format!("invalid dialogue '{}'", dialogue))?;
+
+        if dialogues().get(dialogue).is_none() {
+            return Err(format!("unknown dialogue '{}'", dialogue));
+        }
+
+        Ok(Self {
+            name: name.trim().to_string(),
+            dialogue: dialogue.to_string(),
+        })
+    }
+
+    pub fn to_code(&self) -> String {
+        format!("@{} {}", self.dialogue, self.name)
+    }
+}
+
+pub struct Dialogues {
+    dialogues: HashMap<String, Dialogue>,
+}
+
+/// Dialogues of all NPCs, which map files refer to by name. Falls back to dialogues file
+/// when `init` wasn't called.
+pub fn dialogues() -> &'static Dialogues {
+    DIALOGUES.get_or_init(|| {
+        Dialogues::load_from_file(DIALOGUES_FILE)
+            .unwrap_or_else(|error| panic!("{}: {}", DIALOGUES_FILE, error))
+    })
+}
+
+/// Sets dialogues used by the game. It can be done only once, before first `dialogues`.
+pub fn init(dialogues: Dialogues) -> bool {
+    DIALOGUES.set(dialogues).is_ok()
+}
+
+impl Dialogues {
+    pub fn load_from_file(file_name: &str) -> io::Result<Self> {
+        let raw_data = fs::read_to_string(file_name)?;
+        Self::parse(&raw_data).map_err(|error| io::Error::new(ErrorKind::InvalidData, error))
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Dialogue> {
+        self.dialogues.get(name)
+    }
+
+    fn parse(raw_data: &str) -> Result<Self, String> {
+        let mut dialogues: HashMap<String, Dialogue> = HashMap::new();
+        // Dialogue and node names of current section.
+        let mut current: Option<(String, String)> = None;
+
+        for (idx, line) in raw_data.lines().enumerate() {
+            let line_number = idx + 1;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with(';') {
+                continue;
+            }
+            let error = |error: String| format!("line {}: {}", line_number, error);
+
+            if line.starts_with('[') && line.ends_with(']') {
+                let (dialogue, node) = line[1..line.len() - 1]
+                    .split_once('.')
+                    .ok_or_else(|| error("expected [dialogue.node] section".to_string()))?;
+                let (dialogue, node) = (dialogue.trim().to_string(), node.trim().to_string());
+
+                let nodes = &mut dialogues
+                    .entry(dialogue.clone())
+                    .or_insert(Dialogue {
+                        nodes: HashMap::new(),
+                    })
+                    .nodes;
+                if nodes.contains_key(&node) {
+                    return Err(error(format!("duplicate node '{}.{}'", dialogue, node)));
+                }
+                nodes.insert(
+                    node.clone(),
+                    Node {
+                        text: Vec::new(),
+                        choices: Vec::new(),
+                    },
+                );
+                current = Some((dialogue, node));
+                continue;
+            }
+
+            let node = match &current {
+                Some((dialogue, node)) => dialogues
+                    .get_mut(dialogue)
+                    .and_then(|dialogue| dialogue.nodes.get_mut(node))
+                    .unwrap(),
+                None => return Err(error("text outside of section".to_string())),
+            };
+
+            let (name, value) = line
+                .split_once('=')
+                .ok_or_else(|| error("missing '='".to_string()))?;
+            let value = value.trim();
+
+            match name.trim() {
+                "text" => node.text.push(value.to_string()),
+                "choice" => {
+                    let (text, next) = match value.split_once("->") {
+                        Some((text, next)) => (text.trim(), Some(next.trim().to_string())),
+                        None => (value, None),
+                    };
+                    node.choices.push(Choice {
+                        text: text.to_string(),
+                        next,
+                        conditions: Vec::new(),
+                        effects: Vec::new(),
+                    });
+                }
+                "if" | "do" => {
+                    let choice = node
+                        .choices
+                        .last_mut()
+                        .ok_or_else(|| error(format!("'{}' before any choice", name.trim())))?;
+                    if name.trim() == "if" {
+                        choice
+                            .conditions
+                            .push(Condition::parse(value).map_err(error)?);
+                    } else {
+                        choice.effects.push(Effect::parse(value).map_err(error)?);
+                    }
+                }
+                other => return Err(error(format!("unknown property '{}'", other))),
+            }
+        }
+
+        for (name, dialogue) in dialogues.iter() {
+            if !dialogue.nodes.contains_key(START_NODE) {
+                return Err(format!("dialogue '{}' has no '{}' node", name, START_NODE));
+            }
+
+            let missing = dialogue
+                .nodes
+                .values()
+                .flat_map(|node| node.choices.iter())
+                .filter_map(|choice| choice.next.as_ref())
+                .find(|next| !dialogue.nodes.contains_key(*next));
+            if let Some(next) = missing {
+                return Err(format!("dialogue '{}' has no '{}' node", name, next));
+            }
+        }
+
+        Ok(Self { dialogues })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn choices_depend_on_conditions() {
+        let dialogues = Dialogues::parse(
+            "[guard.start]\ntext = Halt!\nchoice = Let me in. -> gate\nif = flag pass\n\
+             if = gold 5\ndo = take_gold 5\nchoice = Bye.\n[guard.gate]\ntext = Go on.\n",
+        )
+        .unwrap();
+        let start = dialogues.get("guard").unwrap().node(START_NODE).unwrap();
+        assert_eq!(start.choices[0].effects, vec![Effect::TakeGold(5)]);
+        assert_eq!(start.choices[1].next, None);
+
+        let mut player = Player::new("Tester".to_string(), 10, 10, 5, 4, 7, 6);
+        let mut flags = Flags::new();
+        flags.set("pass", FlagValue::Bool(true));
+        let journal = Journal::new();
+        assert!(!start.choices[0].is_available(&player, &flags, &journal));
+        player.add_gold(5);
+        assert!(start.choices[0].is_available(&player, &flags, &journal));
+        assert!(!start.choices[0].is_available(&player, &Flags::new(), &journal));
+    }
+
+    #[test]
+    fn stat_conditions_fit_in_byte() {
+        assert_eq!(
+            Condition::parse("strength 255"),
+            Ok(Condition::Stat(Stat::Strength, 255))
+        );
+        assert_eq!(
+            Condition::parse("intelligence 300"),
+            Err("stat value 300 is too big".to_string())
+        );
+    }
+
+    #[test]
+    fn effects_are_written_as_parsed() {
+        for text in ["give_gold 5", "learn_swimming", "give_boat", "trade"].iter() {
+            assert_eq!(Effect::parse(text).unwrap().to_string(), *text);
+        }
+        assert_eq!(Effect::parse("give_boat"), Ok(Effect::GiveBoat));
+    }
+
+    #[test]
+    fn nodes_form_complete_dialogue() {
+        assert_eq!(
+            Dialogues::parse("[guard.gate]\ntext = Go on.\n").err(),
+            Some("dialogue 'guard' has no 'start' node".to_string())
+        );
+        assert_eq!(
+            Dialogues::parse("[guard.start]\nchoice = Hi -> nowhere\n").err(),
+            Some("dialogue 'guard' has no 'nowhere' node".to_string())
+        );
+        assert_eq!(
+            Dialogues::parse("[guard.start]\ntext = Hi\n[guard.start]\n").err(),
+            Some("line 3: duplicate node 'guard.start'".to_string())
+        );
+    }
+
+    #[test]
+    fn conditions_and_effects_follow_choice() {
+        assert_eq!(
+            Dialogues::parse("[guard.start]\ndo = dance\n").err(),
+            Some("line 2: 'do' before any choice".to_string())
+        );
+        assert_eq!(
+            Dialogues::parse("[guard.start]\nchoice = Hi\ndo = dance\n").err(),
+            Some("line 3: unknown effect 'dance'".to_string())
+        );
+    }
+
+    #[test]
+    fn bundled_trader_opens_trade() {
+        let dialogues = Dialogues::load_from_file(DIALOGUES_FILE).unwrap();
+        let start = dialogues.get("trader").unwrap().node(START_NODE).unwrap();
+        assert!(start
+            .choices
+            .iter()
+            .any(|choice| choice.effects.contains(&Effect::Trade)));
+    }
+}
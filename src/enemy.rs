@@ -1,28 +1,148 @@
+use crate::{
+    loot::{self, Loot},
+    player::Player,
+};
 use core::fmt;
-use rand::Rng;
+use rand::{seq::SliceRandom, Rng};
+
+/// Loot table rolled when enemy is defeated.
+const DROP_TABLE: &str = "enemy";
+
+/// Kinds of enemies met during exploration. Quests refer to enemies by kind.
+pub const KINDS: [&str; 4] = ["rat", "goblin", "skeleton", "bandit"];
+/// Battle ends without winner after so many rounds, e.g. when nobody can hurt the other.
+pub const MAX_BATTLE_ROUNDS: u32 = 50;
+
+/// Short description of enemy kind shown in enemy panel and bestiary.
+pub fn description(kind: &str) -> &'static str {
+    match kind {
+        "rat" => "Big, hungry rat. Weak alone, but rarely alone.",
+        "goblin" => "Small and sneaky, carries whatever it could steal.",
+        "skeleton" => "Bones held together by old magic. Doesn't feel pain.",
+        "bandit" => "Outlaw looking for easy prey and heavy purses.",
+        _ => "Nobody knows much about it.",
+    }
+}
+
+/// How dangerous fight is, judged by health player is expected to lose.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Threat {
+    Harmless,
+    Easy,
+    Fair,
+    Dangerous,
+    Deadly,
+}
+
+impl Threat {
+    /// Threat of losing `lost` health out of `health`.
+    fn of(lost: u32, health: u32) -> Self {
+        let percent = lost * 100 / health.max(1);
+        match percent {
+            0 => Self::Harmless,
+            1..=24 => Self::Easy,
+            25..=49 => Self::Fair,
+            50..=99 => Self::Dangerous,
+            _ => Self::Deadly,
+        }
+    }
+}
+
+/// Expected course of fight between player and enemy.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Estimate {
+    /// Damage player deals every round.
+    pub dealt: u8,
+    /// Damage enemy deals every round.
+    pub taken: u8,
+    /// Rounds needed to defeat enemy, None if player can't hurt it.
+    pub rounds: Option<u32>,
+    pub threat: Threat,
+}
+
+/// How battle ended.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Outcome {
+    Victory,
+    Defeat,
+    /// Nobody was defeated before `MAX_BATTLE_ROUNDS` passed.
+    Stalemate,
+}
+
+/// Course of fought battle.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Battle {
+    pub outcome: Outcome,
+    pub rounds: u32,
+    /// Health lost by player.
+    pub lost: u8,
+}
+
+/// Player and enemy hit each other until one of them is defeated. Player hits first.
+pub fn battle(player: &mut Player, enemy: &mut Enemy) -> Battle {
+    let health = player.current_health();
+    let mut rounds = 0;
+    while rounds < MAX_BATTLE_ROUNDS && !enemy.is_dead() && !player.is_dead() {
+        enemy.hit_by(player.attack());
+        if !enemy.is_dead() {
+            player.hit_by(enemy.attack());
+        }
+        rounds += 1;
+    }
+
+    let outcome = if enemy.is_dead() {
+        Outcome::Victory
+    } else if player.is_dead() {
+        Outcome::Defeat
+    } else {
+        Outcome::Stalemate
+    };
+
+    Battle {
+        outcome,
+        rounds,
+        lost: health - player.current_health(),
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct Enemy {
+    kind: String,
     health: u8,
+    max_health: u8,
     attack: u8,
     defense: u8,
+    drop_table: String,
 }
 
 impl Enemy {
     pub fn new() -> Self {
         let mut rng = rand::thread_rng();
 
+        let health = rng.gen_range(5..=15);
+
         Self {
-            health: rng.gen_range(5..=15),
+            kind: KINDS.choose(&mut rng).unwrap().to_string(),
+            health,
+            max_health: health,
             attack: rng.gen_range(1..=5),
             defense: rng.gen_range(0..=5),
+            drop_table: DROP_TABLE.to_string(),
         }
     }
 
+    pub fn kind(&self) -> &str {
+        &self.kind
+    }
+
     pub fn health(&self) -> u8 {
         self.health
     }
 
+    pub fn max_health(&self) -> u8 {
+        self.max_health
+    }
+
     pub fn attack(&self) -> u8 {
         self.attack
     }
@@ -31,6 +151,23 @@ impl Enemy {
         self.defense
     }
 
+    pub fn drop_table(&self) -> &str {
+        &self.drop_table
+    }
+
+    /// Rolls loot dropped by defeated enemy. Enemies which are still alive drop nothing.
+    pub fn drop_loot(&self, rng: &mut impl Rng) -> Option<Loot> {
+        if !self.is_dead() {
+            return None;
+        }
+
+        let loot = loot::tables()
+            .get(self.drop_table())
+            .map(|table| table.roll(rng))
+            .unwrap_or_default();
+        Some(loot)
+    }
+
     pub fn hit_by(&mut self, attack: u8) {
         if self.defense >= attack {
             return;
@@ -47,16 +184,118 @@ impl Enemy {
     pub fn is_dead(&self) -> bool {
         self.health == 0
     }
+
+    /// Estimates fight with player having given attack, defense and health. Player hits
+    /// first, so defeated enemy doesn't hit back in the last round.
+    pub fn estimate(&self, attack: u8, defense: u8, health: u8) -> Estimate {
+        let dealt = attack.saturating_sub(self.defense);
+        let taken = self.attack.saturating_sub(defense);
+        let rounds = match dealt {
+            0 => None,
+            dealt => Some((self.health as u32).div_ceil(dealt as u32)),
+        };
+        let hits = rounds.map_or(MAX_BATTLE_ROUNDS, |rounds| {
+            rounds.min(MAX_BATTLE_ROUNDS) - 1
+        });
+
+        Estimate {
+            dealt,
+            taken,
+            rounds,
+            threat: Threat::of(hits * taken as u32, health as u32),
+        }
+    }
 }
 
 impl fmt::Display for Enemy {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "H: ({}), A: ({}), D: ({})",
+            "{} H: ({}), A: ({}), D: ({})",
+            self.kind,
             self.health(),
             self.attack(),
             self.defense()
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{item::Item, map::Map, vector::Vec2};
+    use rand::{rngs::StdRng, SeedableRng};
+
+    fn goblin() -> Enemy {
+        Enemy {
+            kind: "goblin".to_string(),
+            health: 10,
+            max_health: 10,
+            attack: 4,
+            defense: 1,
+            drop_table: DROP_TABLE.to_string(),
+        }
+    }
+
+    #[test]
+    fn fight_is_estimated() {
+        let enemy = goblin();
+
+        let estimate = enemy.estimate(6, 2, 20);
+        assert_eq!((estimate.dealt, estimate.taken), (5, 2));
+        assert_eq!(estimate.rounds, Some(2));
+        assert_eq!(estimate.threat, Threat::Easy);
+
+        assert_eq!(enemy.estimate(6, 4, 20).threat, Threat::Harmless);
+        assert_eq!(enemy.estimate(2, 2, 20).threat, Threat::Dangerous);
+        assert_eq!(enemy.estimate(1, 0, 20).rounds, None);
+        assert_eq!(enemy.estimate(1, 0, 20).threat, Threat::Deadly);
+    }
+
+    #[test]
+    fn won_battle_drops_loot_on_floor() {
+        let mut player = Player::new("Tester".to_string(), 20, 10, 6, 2, 7, 6);
+        let mut enemy = goblin();
+        let result = battle(&mut player, &mut enemy);
+
+        assert_eq!(result.outcome, Outcome::Victory);
+        assert_eq!((result.rounds, result.lost), (2, 2));
+        assert!(enemy.is_dead());
+        assert_eq!(player.current_health(), 18);
+
+        let mut rng = StdRng::seed_from_u64(0);
+        let loot = enemy.drop_loot(&mut rng).unwrap();
+        assert!((2..=10).contains(&loot.gold));
+
+        // Dropped items join items already lying on the floor.
+        let mut map = Map::parse_data("###\n#@#\n###\n".to_string(), String::new());
+        let position = Vec2::new(1, 1);
+        map.drop_items(position, vec![Item::lockpick()]);
+        map.drop_items(position, loot.items.clone());
+        let mut expected = vec![Item::lockpick()];
+        expected.extend(loot.items);
+        assert_eq!(
+            map.container(position).unwrap().items(),
+            expected.as_slice()
+        );
+    }
+
+    #[test]
+    fn lost_battle_drops_nothing() {
+        let mut player = Player::new("Tester".to_string(), 5, 10, 1, 0, 7, 6);
+        let mut enemy = goblin();
+        let result = battle(&mut player, &mut enemy);
+
+        assert_eq!(result.outcome, Outcome::Defeat);
+        assert_eq!((result.rounds, result.lost), (2, 5));
+        assert!(player.is_dead());
+        assert!(enemy.drop_loot(&mut StdRng::seed_from_u64(0)).is_none());
+
+        // Enemy with defense higher than player attack can't be hurt.
+        let mut player = Player::new("Tester".to_string(), 50, 10, 1, 5, 7, 6);
+        let result = battle(&mut player, &mut goblin());
+        assert_eq!(result.outcome, Outcome::Stalemate);
+        assert_eq!(result.rounds, MAX_BATTLE_ROUNDS);
+    }
+}
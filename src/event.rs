@@ -1,6 +1,6 @@
 use rand::Rng;
 
-use crate::{enemy::Enemy, item::Item, vector::Vec2};
+use crate::{enemy::Enemy, item::Item, map::trap::TrapKind, vector::Vec2};
 use std::collections::HashMap;
 
 const BASE_EVENT_CHANCE: u8 = 20;
@@ -10,9 +10,11 @@ const ITEM_FIND_CHANCE: u8 = 20;
 pub enum EventType {
     Item(Item),
     Enemy(Enemy),
+    Trap(TrapKind),
 }
 
-struct VistDetails {
+/// What happened on visited tile.
+pub struct VistDetails {
     count: u8,
     item_found: bool,
     enemy_meet: bool,
@@ -26,6 +28,19 @@ impl VistDetails {
             enemy_meet: false,
         }
     }
+
+    /// Number of times player entered tile, up to 255.
+    pub fn count(&self) -> u8 {
+        self.count
+    }
+
+    pub fn item_found(&self) -> bool {
+        self.item_found
+    }
+
+    pub fn enemy_met(&self) -> bool {
+        self.enemy_meet
+    }
 }
 
 pub struct Event {
@@ -55,7 +70,18 @@ impl Event {
         self.event_taken = false;
     }
 
+    /// Queues event caused by game world, like triggered trap. It is returned by the next
+    /// `get_event` call before any random event.
+    pub fn trigger(&mut self, event_type: EventType) {
+        self.event_type = Some(event_type);
+    }
+
     pub fn get_event(&mut self) -> Option<EventType> {
+        if let Some(event_type) = self.event_type.take() {
+            self.event_taken = true;
+            return Some(event_type);
+        }
+
         if self.event_taken {
             return None;
         }
@@ -101,6 +127,18 @@ impl Event {
         }
     }
 
+    pub fn is_visited(&self, pos: Vec2) -> bool {
+        self.visited.contains_key(&pos)
+    }
+
+    pub fn visited_positions(&self) -> impl Iterator<Item = Vec2> + '_ {
+        self.visited.keys().copied()
+    }
+
+    pub fn visits(&self) -> impl Iterator<Item = (Vec2, &VistDetails)> {
+        self.visited.iter().map(|(pos, details)| (*pos, details))
+    }
+
     pub fn in_progress(&self) -> bool {
         self.event_taken
     }
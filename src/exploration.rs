@@ -0,0 +1,145 @@
+use crate::{
+    event::Event,
+    map::{pathfinding, Map, Mobility},
+    vector::Vec2,
+};
+use std::collections::{BTreeMap, HashSet};
+
+/// Statistics are gathered for square regions of map with sides this long.
+pub const REGION_SIZE: i32 = 10;
+
+/// How much of map or its region player explored and what was found there.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RegionStats {
+    /// Top left tile of region.
+    pub origin: Vec2,
+    /// Tiles player can walk to from current position.
+    pub reachable: usize,
+    /// Reachable tiles player has visited.
+    pub explored: usize,
+    /// Visited tiles where item was found.
+    pub items: usize,
+    /// Visited tiles where enemy was met.
+    pub enemies: usize,
+}
+
+impl RegionStats {
+    fn new(origin: Vec2) -> Self {
+        Self {
+            origin,
+            reachable: 0,
+            explored: 0,
+            items: 0,
+            enemies: 0,
+        }
+    }
+
+    pub fn percent_explored(&self) -> u32 {
+        match self.reachable {
+            0 => 0,
+            reachable => (self.explored * 100 / reachable) as u32,
+        }
+    }
+}
+
+/// Exploration statistics of map, used to tune density of encounters.
+#[derive(Debug)]
+pub struct ExplorationStats {
+    total: RegionStats,
+    /// Regions with any reachable or visited tile, ordered by rows.
+    regions: Vec<RegionStats>,
+}
+
+impl ExplorationStats {
+    /// Gathers statistics of tiles visited by player. Reachable tiles are counted from
+    /// `origin`, usually player position, with player mobility.
+    pub fn new(map: &Map, event: &Event, origin: Vec2, mobility: Mobility) -> Self {
+        let reachable = pathfinding::reachable(map, origin, mobility);
+        let visits = event
+            .visits()
+            .map(|(pos, details)| (pos, details.item_found(), details.enemy_met()));
+        Self::from_visits(reachable, visits)
+    }
+
+    /// Counts reachable tiles and visits `(position, item found, enemy met)` per region.
+    fn from_visits(reachable: Vec<Vec2>, visits: impl Iterator<Item = (Vec2, bool, bool)>) -> Self {
+        let mut regions = BTreeMap::new();
+        for pos in reachable.iter() {
+            region(&mut regions, *pos).reachable += 1;
+        }
+
+        let reachable: HashSet<Vec2> = reachable.into_iter().collect();
+        for (pos, item_found, enemy_met) in visits {
+            let stats = region(&mut regions, pos);
+            if reachable.contains(&pos) {
+                stats.explored += 1;
+            }
+            stats.items += item_found as usize;
+            stats.enemies += enemy_met as usize;
+        }
+
+        let regions: Vec<RegionStats> = regions.into_values().collect();
+        let total = regions
+            .iter()
+            .fold(RegionStats::new(Vec2::ZERO), |total, region| RegionStats {
+                origin: Vec2::ZERO,
+                reachable: total.reachable + region.reachable,
+                explored: total.explored + region.explored,
+                items: total.items + region.items,
+                enemies: total.enemies + region.enemies,
+            });
+
+        Self { total, regions }
+    }
+
+    /// Statistics of the whole map.
+    pub fn total(&self) -> RegionStats {
+        self.total
+    }
+
+    pub fn regions(&self) -> &[RegionStats] {
+        &self.regions
+    }
+}
+
+/// Stats of region containing position. Regions are keyed by row first, so they are
+/// ordered like text.
+fn region(regions: &mut BTreeMap<(i32, i32), RegionStats>, pos: Vec2) -> &mut RegionStats {
+    let key = (pos.y.div_euclid(REGION_SIZE), pos.x.div_euclid(REGION_SIZE));
+    let origin = Vec2::new(key.1 * REGION_SIZE, key.0 * REGION_SIZE);
+    regions
+        .entry(key)
+        .or_insert_with(|| RegionStats::new(origin))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn visits_are_counted_per_region() {
+        let reachable: Vec<Vec2> = (0..20).map(|x| Vec2::new(x, 0)).collect();
+        let visits = vec![
+            (Vec2::new(0, 0), false, false),
+            (Vec2::new(1, 0), true, true),
+            (Vec2::new(12, 0), false, true),
+            // Tile which can't be reached any more, e.g. behind locked door.
+            (Vec2::new(12, 15), true, false),
+        ];
+        let stats = ExplorationStats::from_visits(reachable, visits.into_iter());
+
+        let total = stats.total();
+        assert_eq!((total.reachable, total.explored), (20, 3));
+        assert_eq!((total.items, total.enemies), (2, 2));
+        assert_eq!(total.percent_explored(), 15);
+
+        let regions = stats.regions();
+        assert_eq!(regions.len(), 3);
+        assert_eq!(regions[0].origin, Vec2::new(0, 0));
+        assert_eq!(regions[0].percent_explored(), 20);
+        assert_eq!(regions[1].origin, Vec2::new(10, 0));
+        assert_eq!(regions[1].enemies, 1);
+        assert_eq!(regions[2].origin, Vec2::new(10, 10));
+        assert_eq!(regions[2].percent_explored(), 0);
+    }
+}
@@ -0,0 +1,170 @@
+use core::fmt;
+use std::collections::BTreeMap;
+
+/// Value of world state flag, like "door_opened = true" or "rats_killed = 3".
+#[derive(Debug, Clone, PartialEq)]
+pub enum FlagValue {
+    Bool(bool),
+    Int(i64),
+    Text(String),
+}
+
+impl FlagValue {
+    /// Parses `true`, `false`, integer or text. Text can be quoted, e.g. to keep `"5"` as text.
+    pub fn parse(text: &str) -> Self {
+        let text = text.trim();
+        if let Ok(value) = text.parse() {
+            return FlagValue::Bool(value);
+        }
+        if let Ok(value) = text.parse() {
+            return FlagValue::Int(value);
+        }
+
+        let unquoted = text
+            .strip_prefix('"')
+            .and_then(|text| text.strip_suffix('"'))
+            .unwrap_or(text);
+        FlagValue::Text(unquoted.to_string())
+    }
+
+    /// True for `true`, non-zero numbers and non-empty texts.
+    pub fn is_set(&self) -> bool {
+        match self {
+            FlagValue::Bool(value) => *value,
+            FlagValue::Int(value) => *value != 0,
+            FlagValue::Text(value) => !value.is_empty(),
+        }
+    }
+
+    /// Text form, which is read back by `parse` as the same value.
+    pub fn to_code(&self) -> String {
+        match self {
+            FlagValue::Text(value) => format!("\"{}\"", value),
+            other => other.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for FlagValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FlagValue::Bool(value) => write!(f, "{}", value),
+            FlagValue::Int(value) => write!(f, "{}", value),
+            FlagValue::Text(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+/// World state shared by maps, dialogues and quests. Missing flags are treated as unset.
+#[derive(Debug, Default)]
+pub struct Flags {
+    values: BTreeMap<String, FlagValue>,
+}
+
+impl Flags {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&FlagValue> {
+        self.values.get(name)
+    }
+
+    pub fn is_set(&self, name: &str) -> bool {
+        self.get(name).is_some_and(FlagValue::is_set)
+    }
+
+    pub fn set(&mut self, name: &str, value: FlagValue) {
+        self.values.insert(name.to_string(), value);
+    }
+
+    /// Adds number to integer flag. Missing or non-integer flag is counted as 0.
+    pub fn add(&mut self, name: &str, amount: i64) {
+        let current = match self.get(name) {
+            Some(FlagValue::Int(value)) => *value,
+            _ => 0,
+        };
+        self.set(name, FlagValue::Int(current.saturating_add(amount)));
+    }
+
+    pub fn remove(&mut self, name: &str) {
+        self.values.remove(name);
+    }
+
+    /// Writes flags as lines in format: `name = value`, sorted by name.
+    pub fn to_lines(&self) -> Vec<String> {
+        self.values
+            .iter()
+            .map(|(name, value)| format!("{} = {}", name, value.to_code()))
+            .collect()
+    }
+
+    /// Reads back line written by `to_lines`.
+    pub fn parse_line(&mut self, line: &str) -> Result<(), String> {
+        let (name, value) = line.split_once('=').ok_or("missing '=' in flag")?;
+        let name = name.trim();
+        if name.is_empty() || name.contains(char::is_whitespace) {
+            return Err(format!("invalid flag name '{}'", name));
+        }
+
+        self.set(name, FlagValue::parse(value));
+        Ok(())
+    }
+
+    /// Runs debug console command and returns lines of its output.
+    pub fn run_command(&mut self, command: &str) -> Vec<String> {
+        let parts: Vec<&str> = command.split_whitespace().collect();
+        match parts.as_slice() {
+            ["flags"] if self.values.is_empty() => vec!["No flags set".to_string()],
+            ["flags"] => self.to_lines(),
+            ["get", name] => match self.get(name) {
+                Some(value) => vec![format!("{} = {}", name, value.to_code())],
+                None => vec![format!("{} is not set", name)],
+            },
+            ["set", name, value @ ..] if !value.is_empty() => {
+                let value = FlagValue::parse(&value.join(" "));
+                let line = format!("{} = {}", name, value.to_code());
+                self.set(name, value);
+                vec![line]
+            }
+            ["unset", name] => {
+                self.remove(name);
+                vec![format!("{} removed", name)]
+            }
+            _ => vec!["Commands: flags, get <name>, set <name> <value>, unset <name>".to_string()],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flag_values() {
+        assert_eq!(FlagValue::parse("true"), FlagValue::Bool(true));
+        assert_eq!(FlagValue::parse("-3"), FlagValue::Int(-3));
+        assert_eq!(FlagValue::parse("\"5\""), FlagValue::Text("5".to_string()));
+        assert!(!FlagValue::parse("0").is_set());
+
+        let mut flags = Flags::new();
+        flags.add("rats", 2);
+        flags.parse_line("elder = \"Tom\"").unwrap();
+        assert!(flags.is_set("rats"));
+        assert!(!flags.is_set("boss_killed"));
+        assert_eq!(
+            flags.to_lines(),
+            vec!["elder = \"Tom\"".to_string(), "rats = 2".to_string()]
+        );
+        assert!(flags.parse_line("bad name = 1").is_err());
+    }
+
+    #[test]
+    fn console_commands() {
+        let mut flags = Flags::new();
+        assert_eq!(flags.run_command("set door open"), vec!["door = \"open\""]);
+        assert_eq!(flags.run_command("get door"), vec!["door = \"open\""]);
+        flags.run_command("unset door");
+        assert_eq!(flags.run_command("flags"), vec!["No flags set"]);
+    }
+}
@@ -1,70 +1,193 @@
 use crate::{
-    enemy::Enemy,
+    bestiary::Bestiary,
+    container,
+    dialogue::{self, Condition, Effect, Stat},
+    enemy::{self, Enemy, Outcome},
     event::{Event, EventType},
-    item::Item,
-    map::Map,
+    exploration::ExplorationStats,
+    flags::Flags,
+    item::ItemKind,
+    map::{
+        pathfinding,
+        registry::{key, Hook},
+        trap::{self, TrapKind},
+        Map, MapTile,
+    },
+    message_log::{Category, MessageLog},
     player::Player,
-    ui::{self, EventWindow, SimplePlayerScr, Size, TextScr},
+    quest::{self, Journal, QuestEvent, Reward},
+    save,
+    script::{self, ScriptApi, ScriptHook},
+    theme::Theme,
+    travel::{Landmark, Travel},
+    ui::{
+        self, BestiaryWindow, CharacterWindow, ConsoleWindow, EventWindow, ExplorationWindow,
+        JournalWindow, LogWindow, MainLayout, MapWindow, Marker, Response, SimplePlayerScr, Size,
+        TextScr, TradeSide, TradeWindow, TransferWindow,
+    },
     vector::Vec2,
 };
-use console_engine::{pixel, ConsoleEngine, KeyCode};
+use console_engine::{
+    pixel::{self, Pixel},
+    screen::Screen,
+    Color, ConsoleEngine, KeyCode, KeyModifiers,
+};
+use rand::{seq::SliceRandom, Rng};
+use std::collections::HashSet;
+
+/// Help shown in inventory window.
+const INVENTORY_HELP: &str = "Enter use  Esc close";
+/// Width of trade and console windows, narrower map area makes them narrower too.
+const WINDOW_WIDTH: u32 = 56;
+/// Size of minimap in the corner of map area, together with its frame.
+const MINIMAP_WIDTH: u32 = 22;
+const MINIMAP_HEIGHT: u32 = 10;
+
+/// Dialogue with NPC in progress.
+struct Talk {
+    position: Vec2,
+    dialogue: String,
+    node: String,
+    /// Indexes of node choices shown to player, choices with unmet conditions are skipped.
+    choices: Vec<usize>,
+}
 
 #[derive(Debug)]
 enum GameState {
     Normal,
     Decision,
+    Message,
+    ChooseDestination,
+    Container,
+    Trade,
+    Dialogue,
+    Journal,
+    Console,
     Battle,
     Inventory,
+    Log,
+    Character,
+    Bestiary,
+    Overview,
+    Exploration,
 }
 
 pub struct Game {
     player: Player,
     engine: ConsoleEngine,
+    /// Screen areas of map and side panel, computed from terminal size.
+    layout: MainLayout,
+    /// Side panel is shown over the map on narrow terminals.
+    panel_shown: bool,
+    minimap_shown: bool,
+    /// Map tiles are colored by number of visits.
+    heatmap_shown: bool,
     map: Map,
+    theme: Theme,
     event: Event,
     event_window: Option<EventWindow>,
+    /// Opened container position and window showing its items.
+    container_window: Option<(Vec2, TransferWindow)>,
+    /// Window listing player items, which can be used.
+    inventory_window: Option<TransferWindow>,
+    /// Position of merchant player trades with and trade window.
+    trade_window: Option<(Vec2, TradeWindow)>,
+    talk: Option<Talk>,
+    /// World state shared by maps, dialogues and quests.
+    flags: Flags,
+    console: ConsoleWindow,
+    journal: Journal,
+    /// Messages waiting until player closes current window, like completed quests.
+    pending_messages: Vec<(String, String)>,
+    log: MessageLog,
+    log_window: Option<LogWindow>,
+    character_window: Option<CharacterWindow>,
     enemy: Option<Enemy>,
+    /// Enemy kinds met and killed by player.
+    bestiary: Bestiary,
+    bestiary_window: Option<BestiaryWindow>,
     //item: Option<Item>,
+    travel: Option<Travel>,
+    /// Places remembered by player, also shown as notes on map overview.
+    landmarks: Vec<Landmark>,
+    map_window: Option<MapWindow>,
+    exploration_window: Option<ExplorationWindow>,
+    selected_landmark: usize,
+    cursor: Vec2,
     state: GameState,
     is_running: bool,
 }
 
+/// Game fills the whole terminal and adapts its layout when terminal is resized.
 pub struct EngineParams {
-    width: u32,
-    height: u32,
     fps: u32,
 }
 
 impl EngineParams {
+    /// Smaller terminals show only a message asking to resize them.
     pub const MIN_WIDTH: u32 = 40;
     pub const MIN_HEIGHT: u32 = 20;
     pub const MIN_FPS: u32 = 4;
 
-    pub fn new(width: u32, height: u32, fps: u32) -> Self {
+    pub fn new(fps: u32) -> Self {
         Self {
-            width: width.max(Self::MIN_WIDTH),
-            height: height.max(Self::MIN_HEIGHT),
             fps: fps.max(Self::MIN_FPS),
         }
     }
 }
 
 impl Game {
-    pub fn new(engine_params: EngineParams, player: Player, map: Map) -> Self {
-        let mut engine =
-            ConsoleEngine::init(engine_params.width, engine_params.height, engine_params.fps);
+    pub fn new(
+        engine_params: EngineParams,
+        player: Player,
+        mut map: Map,
+        theme: Theme,
+        journal: Journal,
+        flags: Flags,
+    ) -> Self {
+        let mut engine = ConsoleEngine::init_fill(engine_params.fps);
         engine.set_title("Text Adventure");
-        //let mut event = Event::new();
-        //event.player_moved(map.player_position());
+        map.render_map(&theme);
+        let mut event = Event::new();
+        event.player_moved(player.position());
+        let landmarks = vec![Landmark::new("Start".to_string(), player.position())];
+        let mut log = MessageLog::new();
+        log.add(Category::System, "Press l to see message log.");
+
+        let layout = MainLayout::new(engine.get_width(), engine.get_height());
 
         Self {
             player,
             engine,
+            layout,
+            panel_shown: false,
+            minimap_shown: false,
+            heatmap_shown: false,
             map,
-            event: Event::new(),
+            theme,
+            event,
             event_window: None,
+            container_window: None,
+            inventory_window: None,
+            trade_window: None,
+            talk: None,
+            flags,
+            console: ConsoleWindow::new(Size::new(WINDOW_WIDTH.min(layout.map.width), 16)),
+            journal,
+            pending_messages: Vec::new(),
+            log,
+            log_window: None,
+            character_window: None,
             enemy: None,
+            bestiary: Bestiary::new(),
+            bestiary_window: None,
             //item: None,
+            travel: None,
+            landmarks,
+            map_window: None,
+            exploration_window: None,
+            selected_landmark: 0,
+            cursor: Vec2::ZERO,
             state: GameState::Normal,
             is_running: true,
         }
@@ -72,95 +195,1172 @@ impl Game {
 
     pub fn is_running(&mut self) -> bool {
         self.engine.wait_frame();
+        if self.engine.get_resize().is_some() {
+            self.engine.check_resize();
+            let (width, height) = (self.engine.get_width(), self.engine.get_height());
+            self.layout = MainLayout::new(width, height);
+            self.console
+                .resize(Size::new(WINDOW_WIDTH.min(self.layout.map.width), 16));
+            if let Some(window) = self.log_window.as_mut() {
+                window.resize(Size::new(width, height));
+            }
+            if let Some(window) = self.character_window.as_mut() {
+                window.resize(Size::new(width, height));
+            }
+            if let Some(window) = self.map_window.as_mut() {
+                window.resize(Size::new(width, height));
+            }
+        }
         self.is_running
     }
 
     pub fn handle_input(&mut self) {
+        // Nothing is shown until terminal is big enough.
+        if self.engine.get_width() < EngineParams::MIN_WIDTH
+            || self.engine.get_height() < EngineParams::MIN_HEIGHT
+        {
+            self.is_running = !self.engine.is_key_pressed(KeyCode::Char('q'));
+            return;
+        }
+
         match &self.state {
             GameState::Normal => {
+                if !self.pending_messages.is_empty() {
+                    let (title, text) = self.pending_messages.remove(0);
+                    self.show_message(&title, text);
+                    return;
+                }
+
+                if self.travel.is_some() {
+                    self.travel_step();
+                    return;
+                }
+
                 let direction = if self.engine.is_key_pressed(KeyCode::Char('q')) {
                     self.is_running = false;
                     return;
+                } else if self.engine.is_key_pressed(KeyCode::Char('x')) {
+                    self.travel = Some(Travel::Explore);
+                    return;
+                } else if self.engine.is_key_pressed(KeyCode::Char('t')) {
+                    self.cursor = self.player.position();
+                    self.state = GameState::ChooseDestination;
+                    return;
+                } else if self.engine.is_key_pressed(KeyCode::Char('`')) {
+                    self.state = GameState::Console;
+                    return;
+                } else if self.engine.is_key_pressed(KeyCode::Char('j')) {
+                    self.state = GameState::Journal;
+                    return;
+                } else if self.engine.is_key_pressed(KeyCode::Char('p')) {
+                    self.panel_shown = !self.panel_shown;
+                    return;
+                } else if self.engine.is_key_pressed(KeyCode::Char('l')) {
+                    let size = Size::new(self.engine.get_width(), self.engine.get_height());
+                    self.log_window = Some(LogWindow::new(size));
+                    self.state = GameState::Log;
+                    return;
+                } else if self.engine.is_key_pressed(KeyCode::Char('c')) {
+                    let size = Size::new(self.engine.get_width(), self.engine.get_height());
+                    self.character_window = Some(CharacterWindow::new(size));
+                    self.state = GameState::Character;
+                    return;
+                } else if self.engine.is_key_pressed(KeyCode::Char('o')) {
+                    let size = Size::new(self.engine.get_width(), self.engine.get_height());
+                    self.map_window = Some(MapWindow::new(size, self.player.position()));
+                    self.state = GameState::Overview;
+                    return;
+                } else if self
+                    .engine
+                    .is_key_pressed_with_modifier(KeyCode::Char('M'), KeyModifiers::SHIFT)
+                {
+                    self.minimap_shown = !self.minimap_shown;
+                    return;
+                } else if self.engine.is_key_pressed(KeyCode::Char('e')) {
+                    let map = self.layout.map;
+                    let size = Size::new(map.width.min(44), map.height.min(24));
+                    self.exploration_window = Some(ExplorationWindow::new(size));
+                    self.state = GameState::Exploration;
+                    return;
+                } else if self.engine.is_key_pressed(KeyCode::Char('h')) {
+                    self.heatmap_shown = !self.heatmap_shown;
+                    return;
+                } else if self.engine.is_key_pressed(KeyCode::Char('b')) {
+                    let map = self.layout.map;
+                    let size = Size::new(map.width.min(44), map.height.min(20));
+                    self.bestiary_window = Some(BestiaryWindow::new(size));
+                    self.state = GameState::Bestiary;
+                    return;
+                } else if self.engine.is_key_pressed(KeyCode::Char('i')) {
+                    let window = TransferWindow::new(Size::new(50, 12), "Inventory".to_string())
+                        .with_help(INVENTORY_HELP);
+                    self.inventory_window = Some(window);
+                    self.state = GameState::Inventory;
+                    return;
+                } else if self.engine.is_key_pressed(KeyCode::F(5)) {
+                    let saved = save::save_game(
+                        save::QUICKSAVE_FILE,
+                        &self.map,
+                        &self.player,
+                        &self.journal,
+                        &self.flags,
+                    );
+                    let text = match saved {
+                        Ok(()) => format!("Game saved to {}", save::QUICKSAVE_FILE),
+                        Err(error) => format!("Game not saved: {}", error),
+                    };
+                    self.log.add(Category::System, text.clone());
+                    self.show_message("Save", text);
+                    return;
+                } else if self.engine.is_key_pressed(KeyCode::Char('m')) {
+                    let name = format!("Mark {}", self.landmarks.len());
+                    self.landmarks
+                        .push(Landmark::new(name, self.player.position()));
+                    return;
                 } else {
                     self.get_input_vector()
                 };
 
-                if direction == Vec2::ZERO || !self.map.can_move(self.player.position() + direction)
+                self.move_player(direction);
+            }
+
+            GameState::ChooseDestination => {
+                if self.engine.is_key_pressed(KeyCode::Esc) {
+                    self.state = GameState::Normal;
+                } else if self.engine.is_key_pressed(KeyCode::Tab) && !self.landmarks.is_empty() {
+                    // Jump with cursor between remembered landmarks.
+                    self.selected_landmark = (self.selected_landmark + 1) % self.landmarks.len();
+                    self.cursor = self.landmarks[self.selected_landmark].position;
+                } else if self.engine.is_key_pressed(KeyCode::Enter) {
+                    self.travel = Travel::to(
+                        &self.map,
+                        self.player.position(),
+                        self.cursor,
+                        self.player.mobility(),
+                    );
+                    self.state = GameState::Normal;
+                } else {
+                    let cursor = self.cursor + self.get_input_vector();
+                    if cursor.x >= 0
+                        && cursor.y >= 0
+                        && (cursor.x as usize) < self.map.width()
+                        && (cursor.y as usize) < self.map.height()
+                    {
+                        self.cursor = cursor;
+                    }
+                }
+            }
+
+            GameState::Decision => {
+                let mut event_window = self.event_window.take().unwrap();
+                match event_window.handle_input(&self.engine) {
+                    Some(Response::Chosen(0)) => self.fight(),
+                    Some(_) => self.state = GameState::Normal,
+                    None => self.event_window = Some(event_window),
+                }
+            }
+            GameState::Message => {
+                let engine = &self.engine;
+                let closed = self
+                    .event_window
+                    .as_mut()
+                    .is_none_or(|window| window.handle_input(engine).is_some());
+                if closed {
+                    self.event_window = None;
+                    self.state = GameState::Normal;
+                }
+            }
+            GameState::Container => self.handle_container_input(),
+            GameState::Trade => self.handle_trade_input(),
+            GameState::Dialogue => self.handle_dialogue_input(),
+            GameState::Console => {
+                if self.engine.is_key_pressed(KeyCode::Esc) {
+                    self.state = GameState::Normal;
+                } else if let Some(command) = self.console.handle_input(&self.engine) {
+                    let output = self.flags.run_command(&command);
+                    self.console.add_output(output);
+                    self.update_quests(QuestEvent::FlagsChanged);
+                }
+            }
+            GameState::Journal => {
+                if self.engine.is_key_pressed(KeyCode::Esc)
+                    || self.engine.is_key_pressed(KeyCode::Char('j'))
                 {
-                    return;
+                    self.state = GameState::Normal;
+                }
+            }
+            GameState::Battle => {
+                let engine = &self.engine;
+                let closed = self
+                    .event_window
+                    .as_mut()
+                    .is_none_or(|window| window.handle_input(engine).is_some());
+                if closed {
+                    self.finish_battle();
+                }
+            }
+            GameState::Inventory => self.handle_inventory_input(),
+            GameState::Log => {
+                let closed = match self.log_window.as_mut() {
+                    Some(window) => window.handle_input(&self.engine, &self.log),
+                    None => true,
+                };
+                if closed {
+                    self.log_window = None;
+                    self.state = GameState::Normal;
+                }
+            }
+            GameState::Character => {
+                let closed = match self.character_window.as_mut() {
+                    Some(window) => window.handle_input(&self.engine),
+                    None => true,
+                };
+                if closed {
+                    self.character_window = None;
+                    self.state = GameState::Normal;
+                }
+            }
+            GameState::Bestiary => {
+                let closed = match self.bestiary_window.as_mut() {
+                    Some(window) => window.handle_input(&self.engine, &self.bestiary),
+                    None => true,
+                };
+                if closed {
+                    self.bestiary_window = None;
+                    self.state = GameState::Normal;
+                }
+            }
+            GameState::Overview => {
+                let closed = match self.map_window.as_mut() {
+                    Some(window) => {
+                        window.handle_input(&self.engine, &self.map, &mut self.landmarks)
+                    }
+                    None => true,
+                };
+                if closed {
+                    self.map_window = None;
+                    self.state = GameState::Normal;
+                }
+            }
+            GameState::Exploration => {
+                let stats = ExplorationStats::new(
+                    &self.map,
+                    &self.event,
+                    self.player.position(),
+                    self.player.mobility(),
+                );
+                let closed = match self.exploration_window.as_mut() {
+                    Some(window) => window.handle_input(&self.engine, &stats),
+                    None => true,
+                };
+                if closed {
+                    self.exploration_window = None;
+                    self.state = GameState::Normal;
                 }
+            }
+        }
+    }
 
-                self.enemy = None;
+    pub fn render(&mut self) {
+        self.map.render_changes(&self.theme);
+        self.engine.clear_screen();
 
-                self.player.move_vec(direction);
-                self.event.player_moved(self.player.position());
+        let (width, height) = (self.engine.get_width(), self.engine.get_height());
+        if width < EngineParams::MIN_WIDTH || height < EngineParams::MIN_HEIGHT {
+            let screen = ui::too_small_screen(
+                width,
+                height,
+                EngineParams::MIN_WIDTH,
+                EngineParams::MIN_HEIGHT,
+            );
+            self.engine.print_screen(0, 0, &screen);
+            self.engine.draw();
+            return;
+        }
 
-                if let Some(event) = self.event.get_event() {
-                    match event {
-                        EventType::Item(item) => {
-                            let event_window = EventWindow::new(
-                                Size::new(50, 5),
-                                format!("{}", item),
-                                "You found item".to_string(),
-                                vec!["OK".to_string()],
-                            );
+        match &self.state {
+            GameState::ChooseDestination => self.destination_renderer(),
+            GameState::Log | GameState::Character | GameState::Overview => (),
+            _ => {
+                self.map_renderer();
+                if self.minimap_shown {
+                    self.minimap_renderer();
+                }
+            }
+        }
 
-                            self.event_window = Some(event_window);
-                            self.player.add_to_inventory(item);
-                        }
-                        EventType::Enemy(enemy) => {
-                            self.enemy = Some(enemy);
-                            let event_window = EventWindow::new(
-                                Size::new(50, 5),
-                                format!("{}", self.enemy.as_ref().unwrap()),
-                                "You meet enemy".to_string(),
-                                vec!["Fight".to_string(), "Ignore".to_string()],
-                            );
+        // Choosing destination needs panel to show travel information.
+        let panel_visible = self.layout.docked
+            || self.panel_shown
+            || matches!(self.state, GameState::ChooseDestination);
+        let full_screen = matches!(
+            self.state,
+            GameState::Log | GameState::Character | GameState::Overview
+        );
+        if panel_visible && !full_screen {
+            self.side_panel_renderer();
+        }
 
-                            self.event_window = Some(event_window);
-                            self.state = GameState::Decision
+        match &self.state {
+            GameState::Normal | GameState::ChooseDestination => (),
+            GameState::Decision => {
+                if let Some(event_window) = self.event_window.as_ref() {
+                    let screen = event_window.render(self.theme.border());
+                    match self.enemy.as_ref() {
+                        Some(enemy) => {
+                            let estimate = enemy.estimate(
+                                self.player.attack(),
+                                self.player.defense(),
+                                self.player.current_health(),
+                            );
+                            let record = self.bestiary.record(enemy.kind());
+                            let panel =
+                                ui::enemy_panel(self.theme.border(), enemy, &estimate, record);
+                            self.stacked_window_renderer(&panel, &screen);
                         }
+                        None => self.window_renderer(&screen),
                     }
                 }
             }
+            GameState::Message | GameState::Battle | GameState::Dialogue => {
+                if let Some(event_window) = self.event_window.as_ref() {
+                    let screen = event_window.render(self.theme.border());
+                    self.window_renderer(&screen);
+                }
+            }
+            GameState::Container => {
+                if let Some((position, window)) = self.container_window.as_ref() {
+                    let items = self
+                        .map
+                        .container(*position)
+                        .map_or(&[][..], |container| container.items());
+                    let screen = window.render(self.theme.border(), items);
+                    self.window_renderer(&screen);
+                }
+            }
+            GameState::Trade => {
+                if let Some((position, window)) = self.trade_window.as_ref() {
+                    let stock = self
+                        .map
+                        .merchant(*position)
+                        .map_or(&[][..], |merchant| merchant.stock());
+                    let screen = window.render(
+                        self.theme.border(),
+                        stock,
+                        self.player.inventory(),
+                        self.player.gold(),
+                    );
+                    self.window_renderer(&screen);
+                }
+            }
+            GameState::Console => {
+                let screen = self.console.render(self.theme.border());
+                self.window_renderer(&screen);
+            }
+            GameState::Journal => {
+                let map = self.layout.map;
+                let window = JournalWindow::new(Size::new(map.width.min(56), map.height.min(30)));
+                let screen = window.render(
+                    self.theme.border(),
+                    &self.journal.active_entries(),
+                    &self.journal.completed_entries(),
+                );
+                self.window_renderer(&screen);
+            }
+            GameState::Inventory => {
+                if let Some(window) = self.inventory_window.as_ref() {
+                    let screen = window.render(self.theme.border(), self.player.inventory());
+                    self.window_renderer(&screen);
+                }
+            }
+            GameState::Log => {
+                if let Some(window) = self.log_window.as_ref() {
+                    let screen = window.render(self.theme.border(), &self.log);
+                    self.engine.print_screen(0, 0, &screen);
+                }
+            }
+            GameState::Character => {
+                if let Some(window) = self.character_window.as_ref() {
+                    let screen = window.render(self.theme.border(), &self.player);
+                    self.engine.print_screen(0, 0, &screen);
+                }
+            }
+            GameState::Bestiary => {
+                if let Some(window) = self.bestiary_window.as_ref() {
+                    let screen = window.render(self.theme.border(), &self.bestiary);
+                    self.window_renderer(&screen);
+                }
+            }
+            GameState::Overview => {
+                if let Some(window) = self.map_window.as_ref() {
+                    let explored = ui::explored_tiles(&self.map, self.event.visited_positions());
+                    let markers = self.map_markers(&explored);
+                    let screen = window.render(
+                        self.theme.border(),
+                        &self.map,
+                        &explored,
+                        &markers,
+                        &self.landmarks,
+                    );
+                    self.engine.print_screen(0, 0, &screen);
+                }
+            }
+            GameState::Exploration => {
+                if let Some(window) = self.exploration_window.as_ref() {
+                    let stats = ExplorationStats::new(
+                        &self.map,
+                        &self.event,
+                        self.player.position(),
+                        self.player.mobility(),
+                    );
+                    let screen = window.render(self.theme.border(), &stats);
+                    self.window_renderer(&screen);
+                }
+            }
+        }
 
-            GameState::Decision => {
-                let mut event_window = self.event_window.take().unwrap();
-                event_window.handle_input(&self.engine);
-                if self.engine.is_key_pressed(KeyCode::Enter) {
-                    if event_window.selected() == 0 {
-                        self.state = GameState::Battle;
-                    } else {
-                        self.state = GameState::Normal
+        self.engine.draw();
+    }
+
+    /// Runs hook of the tile player bumps into. Returns true if anything happened.
+    fn interact(&mut self, target: Vec2) -> bool {
+        let scripts = self.map.use_scripts(target, self.player.mobility());
+        if self.run_scripts(target, scripts) {
+            return true;
+        }
+
+        let hook = match self.map.tile_at(target).and_then(|tile| tile.hook()) {
+            Some(hook) => hook,
+            None => return false,
+        };
+
+        match hook {
+            Hook::ReadSign => match self.map.sign_text(target) {
+                Some(text) => {
+                    let text = text.to_string();
+                    self.show_message("Signpost", text);
+                    true
+                }
+                None => false,
+            },
+            Hook::OpenContainer => {
+                let title = match self.map.tile_at(target) {
+                    Some(tile) => capitalize(&tile.key().replace('_', " ")),
+                    None => return false,
+                };
+                self.open_container(target, title);
+                true
+            }
+            Hook::Trade => {
+                if self.map.merchant(target).is_none() {
+                    return false;
+                }
+                // Merchant with dialogue greets player first, trade is opened by its effect.
+                if !self.talk_to(target) {
+                    self.open_trade(target);
+                }
+                true
+            }
+            Hook::Talk => self.talk_to(target),
+        }
+    }
+
+    /// Starts dialogue with NPC standing at given position. Returns false if there is none.
+    fn talk_to(&mut self, position: Vec2) -> bool {
+        let dialogue = match self.map.npc(position) {
+            Some(npc) => npc.dialogue.clone(),
+            None => return false,
+        };
+        self.run_scripts(position, self.map.scripts(position, ScriptHook::Talk));
+        self.update_quests(QuestEvent::Talked(&dialogue));
+        self.show_dialogue_node(position, dialogue, dialogue::START_NODE.to_string());
+        true
+    }
+
+    /// Unlocks container if needed and shows its items.
+    fn open_container(&mut self, position: Vec2, title: String) {
+        let locked = match self.map.container(position) {
+            Some(container) => container.is_locked(),
+            None => {
+                self.show_message(&title, "There is nothing inside.".to_string());
+                return;
+            }
+        };
+
+        if locked && !self.unlock_container(position, &title) {
+            return;
+        }
+
+        let window = TransferWindow::new(Size::new(50, 12), title);
+        self.container_window = Some((position, window));
+        self.state = GameState::Container;
+    }
+
+    /// Opens lock with key or lockpick from inventory. Key is used up, lockpick breaks if
+    /// picking fails. Returns true if container was unlocked.
+    fn unlock_container(&mut self, position: Vec2, title: &str) -> bool {
+        let find = |kind| {
+            self.player
+                .inventory()
+                .iter()
+                .position(|item| item.kind == kind)
+        };
+
+        if let Some(index) = find(ItemKind::Key) {
+            self.player.remove_from_inventory(index);
+        } else if let Some(index) = find(ItemKind::Lockpick) {
+            let chance = container::lockpick_chance(self.player.dexterity());
+            if !rand::thread_rng().gen_bool(chance) {
+                self.player.remove_from_inventory(index);
+                self.show_message(title, "Your lockpick breaks.".to_string());
+                return false;
+            }
+        } else {
+            let text = "It is locked. You need a key or a lockpick.".to_string();
+            self.show_message(title, text);
+            return false;
+        }
+
+        if let Some(container) = self.map.container_mut(position) {
+            container.unlock();
+        }
+        true
+    }
+
+    fn handle_container_input(&mut self) {
+        let (position, mut window) = match self.container_window.take() {
+            Some(opened) => opened,
+            None => {
+                self.state = GameState::Normal;
+                return;
+            }
+        };
+
+        if self.engine.is_key_pressed(KeyCode::Esc) {
+            // Pickup disappears when everything is taken from it.
+            let is_empty = self
+                .map
+                .container(position)
+                .is_none_or(|container| container.items().is_empty());
+            if is_empty && self.map.pickups().contains(&position) {
+                self.map.remove_container(position);
+            }
+
+            self.state = GameState::Normal;
+            return;
+        }
+
+        let inventory = self.player.inventory().len();
+        if let Some(container) = self.map.container_mut(position) {
+            if self.engine.is_key_pressed(KeyCode::Enter) {
+                if let Some(item) = container.take(window.selected()) {
+                    self.log.add(Category::Loot, format!("Took {}", item));
+                    self.player.add_to_inventory(item);
+                }
+            } else if self.engine.is_key_pressed(KeyCode::Char('a')) {
+                for item in container.take_all() {
+                    self.log.add(Category::Loot, format!("Took {}", item));
+                    self.player.add_to_inventory(item);
+                }
+            }
+            window.handle_input(&self.engine, container.items().len());
+        }
+
+        if self.player.inventory().len() != inventory {
+            self.update_quests(QuestEvent::InventoryChanged);
+        }
+
+        self.container_window = Some((position, window));
+    }
+
+    /// Runs scripts with given names at position of their tile. Returns true if any script
+    /// was run.
+    fn run_scripts(&mut self, position: Vec2, names: Vec<String>) -> bool {
+        let mut ran = false;
+        for name in names {
+            if let Some(script) = script::scripts().get(&name) {
+                script.run(&mut ScriptContext {
+                    game: self,
+                    position,
+                });
+                ran = true;
+            }
+        }
+
+        if ran {
+            self.update_quests(QuestEvent::FlagsChanged);
+        }
+        ran
+    }
+
+    /// Runs script of selected item, items without script do nothing.
+    fn handle_inventory_input(&mut self) {
+        let mut window = match self.inventory_window.take() {
+            Some(window) => window,
+            None => {
+                self.state = GameState::Normal;
+                return;
+            }
+        };
+
+        if self.engine.is_key_pressed(KeyCode::Esc)
+            || self.engine.is_key_pressed(KeyCode::Char('i'))
+        {
+            self.state = GameState::Normal;
+            return;
+        }
+
+        if self.engine.is_key_pressed(KeyCode::Enter) {
+            if let Some(item) = self.player.inventory().get(window.selected()) {
+                let name = format!("{}{}", script::ITEM_SCRIPT_PREFIX, item.kind.name());
+                match script::scripts().get(&name) {
+                    Some(script) => {
+                        let position = self.player.position();
+                        script.run(&mut ScriptContext {
+                            game: self,
+                            position,
+                        });
+                        self.update_quests(QuestEvent::InventoryChanged);
                     }
-                } else {
-                    self.event_window = Some(event_window)
+                    None => self
+                        .pending_messages
+                        .push((item.to_string(), "Nothing happens.".to_string())),
                 }
+                // Item script shows its messages after inventory is closed.
+                self.state = GameState::Normal;
+                return;
             }
-            GameState::Battle => (),
-            GameState::Inventory => (),
         }
+
+        window.handle_input(&self.engine, self.player.inventory().len());
+        self.inventory_window = Some(window);
     }
 
-    pub fn render(&mut self) {
-        let width = self.engine.get_width();
-        let height = self.engine.get_height();
-        self.engine
-            .print_screen(0, 0, &ui::main_layout_screen(width, height));
+    /// Moves quests forward and gives rewards of completed ones.
+    fn update_quests(&mut self, event: QuestEvent) {
+        for id in self.journal.update(&event, &self.player, &self.flags) {
+            let quest = match quest::quests().get(&id) {
+                Some(quest) => quest,
+                None => continue,
+            };
 
-        self.engine.print(61, 34, &format!("GS: {:?}", self.state));
+            let mut rewards = Vec::new();
+            for reward in quest.rewards.iter() {
+                match reward {
+                    Reward::Xp(xp) => {
+                        self.player.add_xp(*xp);
+                        rewards.push(format!("{} XP", xp));
+                    }
+                    Reward::Gold(gold) => {
+                        self.player.add_gold(*gold);
+                        rewards.push(format!("{} gold", gold));
+                    }
+                    Reward::Item(item) => {
+                        self.player.add_to_inventory(item.clone());
+                        rewards.push(item.to_string());
+                    }
+                    // Flags are hidden from player, so they aren't listed.
+                    Reward::Flag(name, value) => self.flags.set(name, value.clone()),
+                }
+            }
 
-        match &self.state {
-            GameState::Normal => self.player_details_renderer(),
-            GameState::Decision => {
-                self.engine
-                    .print_screen(4, 19, &self.event_window.as_ref().unwrap().render())
+            let text = if rewards.is_empty() {
+                quest.title.clone()
+            } else {
+                format!("{}. Reward: {}", quest.title, rewards.join(", "))
+            };
+            self.log
+                .add(Category::System, format!("Quest completed: {}", text));
+            self.pending_messages
+                .push(("Quest completed".to_string(), text));
+        }
+    }
+
+    fn open_trade(&mut self, position: Vec2) {
+        self.trade_window = Some((
+            position,
+            TradeWindow::new(Size::new(WINDOW_WIDTH.min(self.layout.map.width), 14)),
+        ));
+        self.state = GameState::Trade;
+    }
+
+    /// Shows text of dialogue node with choices available to player. Dialogue ends when
+    /// node doesn't exist.
+    fn show_dialogue_node(&mut self, position: Vec2, dialogue: String, node: String) {
+        let (title, found) = match self.map.npc(position) {
+            Some(npc) => (
+                npc.name.clone(),
+                dialogue::dialogues()
+                    .get(&dialogue)
+                    .and_then(|dialogue| dialogue.node(&node)),
+            ),
+            None => (String::new(), None),
+        };
+        let found = match found {
+            Some(found) => found,
+            None => {
+                self.talk = None;
+                self.state = GameState::Normal;
+                return;
             }
-            GameState::Battle => (),
-            GameState::Inventory => (),
+        };
+
+        let choices: Vec<usize> = (0..found.choices.len())
+            .filter(|idx| {
+                found.choices[*idx].is_available(&self.player, &self.flags, &self.journal)
+            })
+            .collect();
+        let mut options: Vec<String> = choices
+            .iter()
+            .map(|idx| found.choices[*idx].text.clone())
+            .collect();
+        if options.is_empty() {
+            options.push("Leave.".to_string());
         }
 
-        self.engine.draw();
+        let text = found.text.join("\n\n");
+        self.event_window = Some(EventWindow::new(
+            text,
+            title,
+            options,
+            self.layout.map.width,
+        ));
+        self.talk = Some(Talk {
+            position,
+            dialogue,
+            node,
+            choices,
+        });
+        self.state = GameState::Dialogue;
+    }
+
+    /// Applies effects of picked choice and moves dialogue to the next node.
+    fn handle_dialogue_input(&mut self) {
+        let mut event_window = match self.event_window.take() {
+            Some(event_window) => event_window,
+            None => {
+                self.state = GameState::Normal;
+                return;
+            }
+        };
+        let selected = match event_window.handle_input(&self.engine) {
+            Some(Response::Chosen(selected)) => selected,
+            Some(Response::Cancelled) => {
+                self.talk = None;
+                self.state = GameState::Normal;
+                return;
+            }
+            None => {
+                self.event_window = Some(event_window);
+                return;
+            }
+        };
+
+        let talk = match self.talk.take() {
+            Some(talk) => talk,
+            None => {
+                self.state = GameState::Normal;
+                return;
+            }
+        };
+        let choice = talk.choices.get(selected).and_then(|idx| {
+            dialogue::dialogues()
+                .get(&talk.dialogue)?
+                .node(&talk.node)?
+                .choices
+                .get(*idx)
+        });
+        let choice = match choice {
+            Some(choice) => choice,
+            None => {
+                self.state = GameState::Normal;
+                return;
+            }
+        };
+
+        self.state = GameState::Normal;
+        for effect in choice.effects.iter() {
+            self.apply_effect(effect, talk.position);
+        }
+        self.update_quests(QuestEvent::InventoryChanged);
+
+        // Fight or trade started by effect ends dialogue.
+        if let (GameState::Normal, Some(next)) = (&self.state, &choice.next) {
+            self.show_dialogue_node(talk.position, talk.dialogue, next.clone());
+        }
+    }
+
+    fn apply_effect(&mut self, effect: &Effect, position: Vec2) {
+        match effect {
+            Effect::GiveItem(item) => self.player.add_to_inventory(item.clone()),
+            Effect::TakeItem(item) => {
+                if let Some(index) = self.player.inventory().iter().position(|i| i == item) {
+                    self.player.remove_from_inventory(index);
+                }
+            }
+            Effect::GiveGold(gold) => self.player.add_gold(*gold),
+            Effect::TakeGold(gold) => {
+                let gold = (*gold).min(self.player.gold());
+                self.player.spend_gold(gold);
+            }
+            Effect::SetFlag(name, value) => self.flags.set(name, value.clone()),
+            Effect::AddFlag(name, amount) => self.flags.add(name, *amount),
+            Effect::ClearFlag(name) => self.flags.remove(name),
+            Effect::StartQuest(id) => {
+                if self.journal.start(id) {
+                    if let Some(quest) = quest::quests().get(id) {
+                        let text = format!("{}: {}", quest.title, quest.description);
+                        self.log
+                            .add(Category::System, format!("New quest: {}", quest.title));
+                        self.pending_messages.push(("New quest".to_string(), text));
+                    }
+                    // Objectives may be already fulfilled, e.g. item to fetch is in inventory.
+                    self.update_quests(QuestEvent::InventoryChanged);
+                }
+            }
+            Effect::LearnSwimming => {
+                self.player.learn_swimming();
+                self.log.add(Category::System, "You can swim now.");
+            }
+            Effect::GiveBoat => {
+                self.player.set_boat(true);
+                self.log.add(Category::System, "You got a boat.");
+            }
+            Effect::Fight => {
+                self.enemy = Some(Enemy::new());
+                self.fight();
+            }
+            Effect::Trade => {
+                if self.map.merchant(position).is_some() {
+                    self.open_trade(position);
+                }
+            }
+        }
+    }
+
+    /// Buys item selected on merchant side or sells item selected in player inventory.
+    fn handle_trade_input(&mut self) {
+        let (position, mut window) = match self.trade_window.take() {
+            Some(opened) => opened,
+            None => {
+                self.state = GameState::Normal;
+                return;
+            }
+        };
+
+        if self.engine.is_key_pressed(KeyCode::Esc) {
+            self.state = GameState::Normal;
+            return;
+        }
+
+        let merchant = match self.map.merchant_mut(position) {
+            Some(merchant) => merchant,
+            None => {
+                self.state = GameState::Normal;
+                return;
+            }
+        };
+
+        let traded = self.engine.is_key_pressed(KeyCode::Enter);
+        if traded {
+            let index = window.selected();
+            match window.side() {
+                TradeSide::Merchant => {
+                    if let Some((item, price)) = merchant.sell_to(index, self.player.gold()) {
+                        let text = format!("Bought {} for {} gold", item, price);
+                        self.log.add(Category::Loot, text);
+                        self.player.spend_gold(price);
+                        self.player.add_to_inventory(item);
+                    }
+                }
+                TradeSide::Player => {
+                    if let Some(item) = self.player.inventory().get(index).cloned() {
+                        self.player.remove_from_inventory(index);
+                        let text = format!("Sold {}", item);
+                        let price = merchant.buy_from(item);
+                        self.log
+                            .add(Category::Loot, format!("{} for {} gold", text, price));
+                        self.player.add_gold(price);
+                    }
+                }
+            }
+        }
+
+        let stock = merchant.stock().len();
+        let inventory = self.player.inventory().len();
+        window.handle_input(&self.engine, |side| match side {
+            TradeSide::Merchant => stock,
+            TradeSide::Player => inventory,
+        });
+
+        if traded {
+            self.update_quests(QuestEvent::InventoryChanged);
+        }
+
+        self.trade_window = Some((position, window));
+    }
+
+    /// Moves player by one tile if it is possible. Returns true if any event happened after move.
+    fn move_player(&mut self, direction: Vec2) -> bool {
+        if direction == Vec2::ZERO {
+            return false;
+        }
+
+        let target = self.player.position() + direction;
+        if self.interact(target) {
+            return true;
+        }
+
+        if !self.map.can_move_with(target, self.player.mobility()) {
+            return false;
+        }
+
+        self.enemy = None;
+
+        self.player.move_vec(direction);
+        self.event.player_moved(self.player.position());
+        self.map.pass_turn();
+        for effect in self.map.triggers(target).to_vec() {
+            self.apply_effect(&effect, target);
+        }
+        self.run_scripts(target, self.map.scripts(target, ScriptHook::Enter));
+        self.update_quests(QuestEvent::Moved(target));
+        // Trigger or script started fight or trade. Queued messages are shown by
+        // `handle_input` after the whole move is done.
+        if !matches!(self.state, GameState::Normal) {
+            return true;
+        }
+
+        let damage = self.map.tile_at(target).unwrap().properties().damage;
+        if damage > 0 {
+            self.player.take_damage(damage);
+        }
+
+        if let Some(MapTile::Trap { kind, .. }) = self.map.tile_at(target) {
+            let kind = *kind;
+            self.map.reveal_trap(target);
+            self.event.trigger(EventType::Trap(kind));
+        } else if self
+            .map
+            .container(target)
+            .is_some_and(|pickup| !pickup.items().is_empty())
+        {
+            self.open_container(target, "Pickup".to_string());
+            return true;
+        }
+
+        let event_happened = if let Some(event) = self.event.get_event() {
+            match event {
+                EventType::Item(item) => {
+                    self.log.add(Category::Loot, format!("Found {}", item));
+                    self.show_message("You found item", format!("{}", item));
+                    self.player.add_to_inventory(item);
+                    self.update_quests(QuestEvent::InventoryChanged);
+                }
+                EventType::Enemy(enemy) => self.meet_enemy(enemy, "You meet enemy"),
+                EventType::Trap(kind) => self.spring_trap(kind),
+            }
+
+            true
+        } else {
+            self.detect_traps()
+        };
+        event_happened || !self.pending_messages.is_empty()
+    }
+
+    /// Shows window asking player if enemy should be fought.
+    fn meet_enemy(&mut self, enemy: Enemy, title: &str) {
+        self.log
+            .add(Category::Combat, format!("{}: {}", title, enemy));
+        self.bestiary.encountered(enemy.kind());
+        let event_window = EventWindow::new(
+            format!("Fight the {}?", enemy.kind()),
+            title.to_string(),
+            vec!["[F]ight".to_string(), "[I]gnore".to_string()],
+            self.layout.map.width,
+        );
+
+        self.enemy = Some(enemy);
+        self.event_window = Some(event_window);
+        self.state = GameState::Decision
+    }
+
+    /// Fights met enemy until one of sides is defeated and shows battle result.
+    fn fight(&mut self) {
+        let mut enemy = match self.enemy.take() {
+            Some(enemy) => enemy,
+            None => {
+                self.state = GameState::Normal;
+                return;
+            }
+        };
+
+        let battle = enemy::battle(&mut self.player, &mut enemy);
+        let (title, contents) = match battle.outcome {
+            Outcome::Victory => (
+                "Victory",
+                format!(
+                    "Enemy defeated in {} rounds, you lost {} health.",
+                    battle.rounds, battle.lost
+                ),
+            ),
+            Outcome::Defeat => ("Defeat", "You have been killed.".to_string()),
+            Outcome::Stalemate => ("Stalemate", "Nobody is hurt, enemy runs away.".to_string()),
+        };
+
+        self.log
+            .add(Category::Combat, format!("{}. {}", title, contents));
+        self.enemy = Some(enemy);
+        let event_window = EventWindow::new(
+            contents,
+            title.to_string(),
+            vec!["OK".to_string()],
+            self.layout.map.width,
+        );
+        self.event_window = Some(event_window);
+        self.state = GameState::Battle;
+    }
+
+    /// Ends game after defeat or gives player loot dropped by defeated enemy. Items which
+    /// aren't taken stay on the floor.
+    fn finish_battle(&mut self) {
+        self.event_window = None;
+        self.state = GameState::Normal;
+
+        if self.player.is_dead() {
+            self.is_running = false;
+            return;
+        }
+
+        let enemy = match self.enemy.take() {
+            Some(enemy) => enemy,
+            None => return,
+        };
+        // Only defeated enemies drop loot, the others ran away.
+        let loot = match enemy.drop_loot(&mut rand::thread_rng()) {
+            Some(loot) => loot,
+            None => return,
+        };
+        self.bestiary.killed(enemy.kind());
+        self.update_quests(QuestEvent::Killed(enemy.kind()));
+
+        self.player.add_gold(loot.gold);
+        if loot.gold > 0 {
+            self.log
+                .add(Category::Loot, format!("Found {} gold", loot.gold));
+        }
+
+        if loot.items.is_empty() {
+            self.show_message("Loot", format!("You found {} gold.", loot.gold));
+            return;
+        }
+
+        let position = self.player.position();
+        self.map.drop_items(position, loot.items);
+        self.open_container(position, format!("Loot, {} gold found", loot.gold));
+    }
+
+    /// Applies effect of the trap player stepped on. Damage is dealt by the tile itself.
+    fn spring_trap(&mut self, kind: TrapKind) {
+        match kind {
+            TrapKind::Pit | TrapKind::Spike => {
+                self.log.add(Category::Combat, kind.description());
+                self.show_message("Trap", kind.description().to_string())
+            }
+            TrapKind::Alarm => self.meet_enemy(Enemy::new(), kind.description()),
+            TrapKind::Teleport => {
+                let position = self.player.position();
+                let destinations: Vec<Vec2> =
+                    pathfinding::reachable(&self.map, position, self.player.mobility())
+                        .into_iter()
+                        .filter(|destination| *destination != position)
+                        .collect();
+
+                if let Some(destination) = destinations.choose(&mut rand::thread_rng()) {
+                    self.player.set_position(*destination);
+                    self.event.player_moved(*destination);
+                    self.update_quests(QuestEvent::Moved(*destination));
+                }
+                self.log.add(Category::Combat, kind.description());
+                self.show_message("Trap", kind.description().to_string());
+            }
+        }
+    }
+
+    /// Gives player a chance to notice every hidden trap nearby. Returns true if any
+    /// trap was found.
+    fn detect_traps(&mut self) -> bool {
+        let chance = trap::detection_chance(self.player.intelligence(), self.player.dexterity());
+        let mut rng = rand::thread_rng();
+        let found: Vec<Vec2> = self
+            .map
+            .hidden_traps_near(self.player.position(), trap::DETECTION_RADIUS)
+            .into_iter()
+            .filter(|_| rng.gen_bool(chance))
+            .collect();
+
+        for pos in found.iter() {
+            self.map.reveal_trap(*pos);
+        }
+
+        match found.as_slice() {
+            [] => false,
+            [pos] => {
+                let name = match self.map.tile_at(*pos) {
+                    Some(MapTile::Trap { kind, .. }) => kind.to_string(),
+                    _ => "trap".to_string(),
+                };
+                let text = format!("You notice a {} nearby.", name);
+                self.log.add(Category::System, text.clone());
+                self.show_message("Trap", text);
+                true
+            }
+            _ => {
+                let text = format!("You notice {} traps nearby.", found.len());
+                self.log.add(Category::System, text.clone());
+                self.show_message("Trap", text);
+                true
+            }
+        }
+    }
+
+    /// Shows window with text, which is closed with Enter.
+    fn show_message(&mut self, title: &str, contents: String) {
+        let event_window = EventWindow::new(
+            contents,
+            title.to_string(),
+            vec!["OK".to_string()],
+            self.layout.map.width,
+        );
+
+        self.event_window = Some(event_window);
+        self.state = GameState::Message;
+    }
+
+    /// Makes one step of auto travel. Travel stops when destination is reached, any movement
+    /// key is pressed, event fires, enemy appears or player loses health.
+    fn travel_step(&mut self) {
+        let mut travel = self.travel.take().unwrap();
+        if self.engine.is_key_pressed(KeyCode::Esc) || self.get_input_vector() != Vec2::ZERO {
+            return;
+        }
+
+        let direction = match travel.next_step(
+            &self.map,
+            &self.event,
+            self.player.position(),
+            self.player.mobility(),
+        ) {
+            Some(direction) => direction,
+            None => return,
+        };
+
+        let health = self.player.current_health();
+        let event_happened = self.move_player(direction);
+
+        if !event_happened && self.enemy.is_none() && self.player.current_health() >= health {
+            self.travel = Some(travel);
+        }
     }
 
     fn get_input_vector(&self) -> Vec2 {
@@ -177,31 +1377,249 @@ impl Game {
         }
     }
 
-    fn player_details_renderer(&mut self) {
-        let mut simple_player_scr = SimplePlayerScr::new(19, 10, &self.player);
-        self.engine.print_screen(0, 0, self.map.screen());
-        self.engine.set_pxl(
-            self.player.position().x,
-            self.player.position().y,
-            pixel::pxl(crate::tiles::PLAYER),
+    /// Draws part of the map seen by camera with pickups and player on it.
+    fn map_renderer(&mut self) {
+        let view = self.layout.map;
+        let camera = self.camera();
+        let visible = self.map.screen().extract(
+            camera.x,
+            camera.y,
+            camera.x + view.width as i32 - 1,
+            camera.y + view.height as i32 - 1,
+            pixel::pxl(' '),
+        );
+        self.engine.print_screen(view.x, view.y, &visible);
+
+        if self.heatmap_shown {
+            let heat: Vec<(Vec2, Pixel)> = self
+                .event
+                .visits()
+                .filter_map(|(position, details)| {
+                    let mut pixel = self.map.screen().get_pxl(position.x, position.y).ok()?;
+                    pixel.bg = ui::heat_color(details.count());
+                    Some((position, pixel))
+                })
+                .collect();
+            for (position, pixel) in heat {
+                self.map_pixel_renderer(position, pixel);
+            }
+        }
+
+        let pickup = self.theme.style(key::PICKUP).pixel();
+        for position in self.map.pickups() {
+            self.map_pixel_renderer(position, pickup);
+        }
+
+        self.map_pixel_renderer(
+            self.player.position(),
+            self.theme.style(key::PLAYER).pixel(),
         );
+    }
 
-        self.engine.print_screen(61, 0, simple_player_scr.screen());
+    /// Map position shown in the top left corner of map area. Camera follows travel cursor
+    /// while destination is chosen and player otherwise.
+    fn camera(&self) -> Vec2 {
+        let focus = if matches!(self.state, GameState::ChooseDestination) {
+            self.cursor
+        } else {
+            self.player.position()
+        };
+        let map_size = Size::new(self.map.width() as u32, self.map.height() as u32);
+        ui::camera(self.layout.map, map_size, focus)
     }
 
-    fn item_event_render(&mut self, item: &Item) {
+    /// Draws pixel at map position, if camera sees it.
+    fn map_pixel_renderer(&mut self, position: Vec2, pixel: Pixel) {
+        let view = self.layout.map;
+        let position = position - self.camera();
+        if position.x >= 0
+            && position.y >= 0
+            && position.x < view.width as i32
+            && position.y < view.height as i32
+        {
+            self.engine
+                .set_pxl(view.x + position.x, view.y + position.y, pixel);
+        }
+    }
+
+    /// Draws small overview of the whole map in the top right corner of map area.
+    fn minimap_renderer(&mut self) {
+        let map = self.layout.map;
+        let size = Size::new(map.width.min(MINIMAP_WIDTH), map.height.min(MINIMAP_HEIGHT));
+        let x = map.right() - size.width as i32;
+        let explored = ui::explored_tiles(&self.map, self.event.visited_positions());
+        let markers = self.map_markers(&explored);
+        let screen = ui::minimap(size, self.theme.border(), &self.map, &explored, &markers);
+        self.engine.print_screen(x, map.y, &screen);
+    }
+
+    /// Markers of map overview: player, notes and explored exits, chests and NPCs.
+    fn map_markers(&self, explored: &HashSet<Vec2>) -> Vec<(Vec2, Marker)> {
+        let exits = [key::STAIRS_UP, key::STAIRS_DOWN]
+            .iter()
+            .flat_map(|tile_key| self.map.tile_positions(tile_key))
+            .map(|position| (position, Marker::Exit));
+        let chests = self
+            .map
+            .container_positions()
+            .into_iter()
+            .map(|position| (position, Marker::Chest));
+        let npcs = self
+            .map
+            .npc_positions()
+            .into_iter()
+            .map(|position| (position, Marker::Npc));
+
+        let mut markers: Vec<(Vec2, Marker)> = exits
+            .chain(chests)
+            .chain(npcs)
+            .filter(|(position, _)| explored.contains(position))
+            .collect();
+        markers.extend(
+            self.landmarks
+                .iter()
+                .map(|landmark| (landmark.position, Marker::Note)),
+        );
+        markers.push((self.player.position(), Marker::Player));
+        markers
+    }
+
+    /// Draws side panel with player stats, game state and message log.
+    fn side_panel_renderer(&mut self) {
+        let layout = self.layout;
+        let screen = ui::side_panel_screen(&layout, self.theme.border());
+        self.engine
+            .print_screen(layout.panel.x, layout.panel.y, &screen);
+
+        let stats = layout.stats;
+        let mut simple_player_scr = SimplePlayerScr::new(stats.width, stats.height, &self.player);
+        self.engine
+            .print_screen(stats.x, stats.y, simple_player_scr.screen());
+
+        let state = format!("GS: {:?}", self.state);
+        self.engine
+            .print(layout.info.x, layout.info.bottom() - 1, &state);
+
+        if matches!(self.state, GameState::ChooseDestination) {
+            self.destination_info_renderer();
+        } else {
+            let panel = ui::log_panel(layout.log.size(), &self.log);
+            self.engine.print_screen(layout.log.x, layout.log.y, &panel);
+        }
+    }
+
+    /// Draws window in the middle of map area.
+    fn window_renderer(&mut self, window: &Screen) {
+        let area = self
+            .layout
+            .map
+            .centered(window.get_width(), window.get_height());
+        self.engine.print_screen(area.x, area.y, window);
+    }
+
+    /// Draws two windows one above another in the middle of map area.
+    fn stacked_window_renderer(&mut self, top: &Screen, bottom: &Screen) {
+        let width = top.get_width().max(bottom.get_width());
+        let area = self
+            .layout
+            .map
+            .centered(width, top.get_height() + bottom.get_height());
+        let x = |window: &Screen| area.x + (width as i32 - window.get_width() as i32) / 2;
+        self.engine.print_screen(x(top), area.y, top);
+        self.engine
+            .print_screen(x(bottom), area.y + top.get_height() as i32, bottom);
+    }
+
+    fn destination_renderer(&mut self) {
+        self.map_renderer();
+        self.map_pixel_renderer(
+            self.cursor,
+            pixel::pxl_fbg('X', Color::Yellow, Color::DarkBlue),
+        );
+    }
+
+    /// Draws coordinates, distance and landmark under travel cursor in place of message log.
+    fn destination_info_renderer(&mut self) {
+        let landmark = self
+            .landmarks
+            .iter()
+            .find(|landmark| landmark.position == self.cursor)
+            .map_or("", |landmark| landmark.name.as_str());
+        let distances =
+            pathfinding::distance_map(&self.map, self.player.position(), self.player.mobility());
+        let distance = if distances.is_reachable(self.cursor) {
+            format!(
+                "Travel: {} steps",
+                distances.distance(self.cursor).unwrap_or(0)
+            )
+        } else {
+            "Can't get there".to_string()
+        };
         let contents = vec![
-            "You found:".to_string(),
-            item.to_string(),
-            "Press Enter".to_string(),
+            distance,
+            format!("({}, {}) {}", self.cursor.x, self.cursor.y, landmark),
+            "Tab: landmarks".to_string(),
+            "Enter: go, Esc".to_string(),
         ];
-        let item_screen = TextScr::new(20, 4, contents);
-        self.engine.print_screen(61, 36, item_screen.screen());
+        let info = self.layout.log;
+        let destination_info = TextScr::new(info.width, info.height, contents);
+        self.engine
+            .print_screen(info.x, info.y, destination_info.screen());
     }
+}
+
+/// Gives scripts access to game, effects are applied at position of the script.
+struct ScriptContext<'a> {
+    game: &'a mut Game,
+    position: Vec2,
+}
+
+impl ScriptApi for ScriptContext<'_> {
+    fn is_met(&self, condition: &Condition) -> bool {
+        condition.is_met(&self.game.player, &self.game.flags, &self.game.journal)
+    }
+
+    fn apply(&mut self, effect: &Effect) {
+        self.game.apply_effect(effect, self.position);
+    }
+
+    fn message(&mut self, title: &str, text: &str) {
+        self.game.log.add(Category::System, text);
+        self.game
+            .pending_messages
+            .push((title.to_string(), text.to_string()));
+    }
+
+    fn heal(&mut self, amount: u8) {
+        self.game.player.heal(amount);
+    }
+
+    fn damage(&mut self, amount: u8) {
+        self.game.player.take_damage(amount);
+    }
+
+    fn add_xp(&mut self, xp: u8) {
+        self.game.player.add_xp(xp);
+    }
+
+    fn raise_stat(&mut self, stat: Stat, amount: u8) {
+        self.game.player.raise_stat(stat, amount);
+    }
+
+    fn set_tile(&mut self, position: Vec2, key: &str) {
+        if self.game.map.contains(position) {
+            let tile = MapTile::from_key(key);
+            self.game
+                .map
+                .set_tile(position.x as usize, position.y as usize, tile);
+        }
+    }
+}
 
-    fn enemy_event_renderer(&mut self, enemy: &Enemy) {
-        let contents = vec!["You meet:".to_string(), enemy.to_string()];
-        let enemy_info = TextScr::new(20, 4, contents);
-        self.engine.print_screen(61, 36, enemy_info.screen());
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
     }
 }
@@ -1,7 +1,53 @@
 use core::fmt;
 use rand::Rng;
 
-#[derive(Debug, Clone)]
+const KEY_VALUE: u32 = 20;
+const LOCKPICK_VALUE: u32 = 10;
+const GEAR_BASE_VALUE: i32 = 10;
+const VALUE_PER_MODIFIER: i32 = 6;
+
+/// Names of gear modifiers, in the same order as fields.
+pub const MODIFIER_NAMES: [&str; 8] = [
+    "Health",
+    "Mana",
+    "Attack",
+    "Defense",
+    "Strength",
+    "Dexterity",
+    "Intelligence",
+    "Speed",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ItemKind {
+    /// Equipment with stat modifiers.
+    Gear,
+    /// Opens locked containers and is used up.
+    Key,
+    /// Lets dexterous player open locked containers. Breaks on failure.
+    Lockpick,
+}
+
+impl ItemKind {
+    /// Name used in item codes.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ItemKind::Gear => "gear",
+            ItemKind::Key => "key",
+            ItemKind::Lockpick => "lockpick",
+        }
+    }
+}
+
+/// How many positive modifiers gear has.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub enum Rarity {
+    Common,
+    Uncommon,
+    Rare,
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum Location {
     Head,
     Chest,
@@ -11,8 +57,9 @@ pub enum Location {
     Hand,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Item {
+    pub kind: ItemKind,
     pub location: Location,
     pub health_mod: i8,
     pub mana_mod: i8,
@@ -54,6 +101,7 @@ impl Item {
         };
 
         Self {
+            kind: ItemKind::Gear,
             location,
             health_mod,
             mana_mod,
@@ -66,6 +114,144 @@ impl Item {
         }
     }
 
+    pub fn key() -> Self {
+        Self::tool(ItemKind::Key)
+    }
+
+    pub fn lockpick() -> Self {
+        Self::tool(ItemKind::Lockpick)
+    }
+
+    /// Item without any modifiers.
+    fn tool(kind: ItemKind) -> Self {
+        Self {
+            kind,
+            location: Location::Hand,
+            health_mod: 0,
+            mana_mod: 0,
+            attack_mod: 0,
+            defense_mod: 0,
+            strength_mod: 0,
+            dexterity_mod: 0,
+            intelligence_mod: 0,
+            speed_mod: 0,
+        }
+    }
+
+    /// Modifiers in the same order as `MODIFIER_NAMES`.
+    pub fn modifiers(&self) -> [i8; 8] {
+        [
+            self.health_mod,
+            self.mana_mod,
+            self.attack_mod,
+            self.defense_mod,
+            self.strength_mod,
+            self.dexterity_mod,
+            self.intelligence_mod,
+            self.speed_mod,
+        ]
+    }
+
+    pub fn rarity(&self) -> Rarity {
+        match self.modifiers().iter().filter(|value| **value > 0).count() {
+            0 | 1 => Rarity::Common,
+            2 => Rarity::Uncommon,
+            _ => Rarity::Rare,
+        }
+    }
+
+    /// Price of item paid to merchant. Gear is worth more with higher modifiers and rarity,
+    /// negative modifiers lower its value.
+    pub fn value(&self) -> u32 {
+        match self.kind {
+            ItemKind::Key => KEY_VALUE,
+            ItemKind::Lockpick => LOCKPICK_VALUE,
+            ItemKind::Gear => {
+                let modifiers: i32 = self.modifiers().iter().map(|value| *value as i32).sum();
+                let multiplier = match self.rarity() {
+                    Rarity::Common => 1,
+                    Rarity::Uncommon => 2,
+                    Rarity::Rare => 3,
+                };
+                ((GEAR_BASE_VALUE + modifiers * VALUE_PER_MODIFIER).max(1) * multiplier) as u32
+            }
+        }
+    }
+
+    /// Gold paid by merchant for the item, half of its value.
+    pub fn sell_value(&self) -> u32 {
+        (self.value() / 2).max(1)
+    }
+
+    /// Text form of item used in saves and map files, e.g. `key` or
+    /// `gear head 1 0 -2 0 0 0 0 0`. Gear modifiers are written in the same order as fields.
+    pub fn to_code(&self) -> String {
+        match self.kind {
+            ItemKind::Key | ItemKind::Lockpick => self.kind.name().to_string(),
+            ItemKind::Gear => {
+                let location = match self.location {
+                    Location::Head => "head",
+                    Location::Chest => "chest",
+                    Location::Leg => "leg",
+                    Location::Arm => "arm",
+                    Location::Foot => "foot",
+                    Location::Hand => "hand",
+                };
+                format!(
+                    "{} {} {} {} {} {} {} {} {} {}",
+                    self.kind.name(),
+                    location,
+                    self.health_mod,
+                    self.mana_mod,
+                    self.attack_mod,
+                    self.defense_mod,
+                    self.strength_mod,
+                    self.dexterity_mod,
+                    self.intelligence_mod,
+                    self.speed_mod
+                )
+            }
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Self> {
+        let mut parts = code.split_whitespace();
+        match parts.next()? {
+            "key" => Some(Self::key()),
+            "lockpick" => Some(Self::lockpick()),
+            "gear" => {
+                let location = match parts.next()? {
+                    "head" => Location::Head,
+                    "chest" => Location::Chest,
+                    "leg" => Location::Leg,
+                    "arm" => Location::Arm,
+                    "foot" => Location::Foot,
+                    "hand" => Location::Hand,
+                    _ => return None,
+                };
+
+                let mods: Vec<i8> = parts.map(|part| part.parse().ok()).collect::<Option<_>>()?;
+                if mods.len() != 8 {
+                    return None;
+                }
+
+                Some(Self {
+                    kind: ItemKind::Gear,
+                    location,
+                    health_mod: mods[0],
+                    mana_mod: mods[1],
+                    attack_mod: mods[2],
+                    defense_mod: mods[3],
+                    strength_mod: mods[4],
+                    dexterity_mod: mods[5],
+                    intelligence_mod: mods[6],
+                    speed_mod: mods[7],
+                })
+            }
+            _ => None,
+        }
+    }
+
     fn get_random_parmeter(rng: &mut impl Rng, parameters: &mut u8) -> i8 {
         if *parameters > 0 && rng.gen() {
             let value = rng.gen_range(-3..=3);
@@ -81,6 +267,12 @@ impl Item {
 
 impl fmt::Display for Item {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            ItemKind::Key => return write!(f, "Key"),
+            ItemKind::Lockpick => return write!(f, "Lockpick"),
+            ItemKind::Gear => (),
+        }
+
         let name = match self.location {
             Location::Leg => "Pants",
             Location::Arm => "???",
@@ -118,3 +310,36 @@ impl fmt::Display for Item {
         write!(f, "{}{}", name, params)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn item_codes() {
+        let item = Item::new();
+        assert_eq!(Item::from_code(&item.to_code()), Some(item));
+        assert_eq!(Item::from_code("key"), Some(Item::key()));
+        assert_eq!(
+            Item::from_code("gear leg 1 0 -2 0 0 0 0 3").map(|item| item.speed_mod),
+            Some(3)
+        );
+        assert_eq!(Item::from_code("gear leg 1 0"), None);
+        assert_eq!(Item::from_code("sword"), None);
+    }
+
+    #[test]
+    fn item_values() {
+        let common = Item::from_code("gear head 1 0 0 0 0 0 0 0").unwrap();
+        let rare = Item::from_code("gear head 1 0 2 0 0 0 0 1").unwrap();
+        let cursed = Item::from_code("gear head 0 -3 0 -2 0 0 0 0").unwrap();
+
+        assert_eq!(common.rarity(), Rarity::Common);
+        assert_eq!(rare.rarity(), Rarity::Rare);
+        assert_eq!(common.value(), 16);
+        assert_eq!(rare.value(), 102);
+        assert_eq!(cursed.value(), 1);
+        assert_eq!(cursed.sell_value(), 1);
+        assert_eq!(Item::key().sell_value(), 10);
+    }
+}
@@ -0,0 +1,222 @@
+use crate::item::Item;
+use rand::Rng;
+use std::{
+    collections::HashMap,
+    fs,
+    io::{self, ErrorKind},
+    sync::OnceLock,
+};
+
+pub const LOOT_FILE: &str = "data/loot.def";
+
+static TABLES: OnceLock<LootTables> = OnceLock::new();
+
+pub enum LootItem {
+    /// Equipment with random modifiers, the same as found during exploration.
+    RandomGear,
+    Fixed(Item),
+}
+
+pub struct LootEntry {
+    pub item: LootItem,
+    /// Chance in percents of getting this entry.
+    pub chance: u8,
+    pub count: u8,
+}
+
+/// Items and gold drawn from loot table.
+#[derive(Debug, Default)]
+pub struct Loot {
+    pub items: Vec<Item>,
+    pub gold: u32,
+}
+
+/// List of items which may be found in container or dropped by enemy.
+pub struct LootTable {
+    entries: Vec<LootEntry>,
+    /// Smallest and biggest amount of gold. Gold is given only by enemies.
+    gold: (u32, u32),
+}
+
+impl LootTable {
+    /// Draws items and gold from the table. Every entry is rolled separately.
+    pub fn roll(&self, rng: &mut impl Rng) -> Loot {
+        let mut items = Vec::new();
+        for entry in self.entries.iter() {
+            if rng.gen_range(0..100) >= entry.chance {
+                continue;
+            }
+
+            for _ in 0..entry.count {
+                items.push(match &entry.item {
+                    LootItem::RandomGear => Item::new(),
+                    LootItem::Fixed(item) => item.clone(),
+                });
+            }
+        }
+
+        let (min_gold, max_gold) = self.gold;
+        Loot {
+            items,
+            gold: rng.gen_range(min_gold..=max_gold),
+        }
+    }
+}
+
+pub struct LootTables {
+    tables: HashMap<String, LootTable>,
+}
+
+/// Returns loot tables used by the game. They are loaded from default file when they weren't
+/// set with `init` before.
+pub fn tables() -> &'static LootTables {
+    TABLES.get_or_init(|| {
+        LootTables::load_from_file(LOOT_FILE)
+            .unwrap_or_else(|error| panic!("{}: {}", LOOT_FILE, error))
+    })
+}
+
+/// Sets loot tables used by the game. It can be done only once, before first `tables`.
+pub fn init(tables: LootTables) -> bool {
+    TABLES.set(tables).is_ok()
+}
+
+impl LootTables {
+    pub fn load_from_file(file_name: &str) -> io::Result<Self> {
+        let raw_data = fs::read_to_string(file_name)?;
+        Self::parse(&raw_data).map_err(|error| io::Error::new(ErrorKind::InvalidData, error))
+    }
+
+    pub fn get(&self, name: &str) -> Option<&LootTable> {
+        self.tables.get(name)
+    }
+
+    fn parse(raw_data: &str) -> Result<Self, String> {
+        let mut tables = HashMap::new();
+        let mut current: Option<String> = None;
+
+        for (idx, line) in raw_data.lines().enumerate() {
+            let line_number = idx + 1;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with(';') {
+                continue;
+            }
+
+            if line.starts_with('[') && line.ends_with(']') {
+                let name = line[1..line.len() - 1].trim().to_string();
+                tables.insert(
+                    name.clone(),
+                    LootTable {
+                        entries: Vec::new(),
+                        gold: (0, 0),
+                    },
+                );
+                current = Some(name);
+                continue;
+            }
+
+            let table = match &current {
+                Some(name) => tables.get_mut(name).unwrap(),
+                None => return Err(format!("line {}: text outside of section", line_number)),
+            };
+
+            if line.starts_with("gold") {
+                table.gold = Self::parse_gold(line)
+                    .map_err(|error| format!("line {}: {}", line_number, error))?;
+                continue;
+            }
+
+            let entry = Self::parse_entry(line)
+                .map_err(|error| format!("line {}: {}", line_number, error))?;
+            table.entries.push(entry);
+        }
+
+        Ok(Self { tables })
+    }
+
+    /// Parses gold line in format: `gold = min max`.
+    fn parse_gold(line: &str) -> Result<(u32, u32), String> {
+        let values: Vec<Option<u32>> = line
+            .split_once('=')
+            .ok_or("missing '='")?
+            .1
+            .split_whitespace()
+            .map(|value| value.parse().ok())
+            .collect();
+
+        match values.as_slice() {
+            [Some(min), Some(max)] if min <= max => Ok((*min, *max)),
+            _ => Err("expected smallest and biggest amount of gold".to_string()),
+        }
+    }
+
+    /// Parses entry line in format: `item = chance count`.
+    fn parse_entry(line: &str) -> Result<LootEntry, String> {
+        let mut parts = line.splitn(2, '=');
+        let code = parts.next().unwrap_or("").trim();
+        let values: Vec<&str> = parts
+            .next()
+            .ok_or("missing '='")?
+            .split_whitespace()
+            .collect();
+
+        let item = match code {
+            "gear" => LootItem::RandomGear,
+            _ => match Item::from_code(code) {
+                Some(item) => LootItem::Fixed(item),
+                None => return Err(format!("unknown item '{}'", code)),
+            },
+        };
+
+        let (chance, count) = match values.as_slice() {
+            [chance, count] => (chance.parse().ok(), count.parse().ok()),
+            _ => (None, None),
+        };
+
+        match (chance, count) {
+            (Some(chance), Some(count)) if chance <= 100 => Ok(LootEntry {
+                item,
+                chance,
+                count,
+            }),
+            _ => Err("expected chance (0-100) and count".to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::item::ItemKind;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn roll_loot_table() {
+        let tables = LootTables::parse("[box]\nkey = 100 2\nlockpick = 0 5\ngold = 3 3\n").unwrap();
+        let loot = tables
+            .get("box")
+            .unwrap()
+            .roll(&mut StdRng::seed_from_u64(1));
+
+        assert_eq!(loot.items.len(), 2);
+        assert!(loot.items.iter().all(|item| item.kind == ItemKind::Key));
+        assert_eq!(loot.gold, 3);
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            LootTables::parse("[box]\nsword = 10 1\n").err(),
+            Some("line 2: unknown item 'sword'".to_string())
+        );
+        assert_eq!(
+            LootTables::parse("[box]\ngear = 150 1\n").err(),
+            Some("line 2: expected chance (0-100) and count".to_string())
+        );
+        assert_eq!(
+            LootTables::parse("[box]\ngold = 9 1\n").err(),
+            Some("line 2: expected smallest and biggest amount of gold".to_string())
+        );
+        assert!(LootTables::load_from_file(LOOT_FILE).is_ok());
+    }
+}
@@ -1,29 +1,184 @@
+mod bestiary;
+mod container;
+mod dialogue;
 mod enemy;
 mod event;
+mod exploration;
+mod flags;
 mod game;
 mod item;
+mod loot;
 mod map;
+mod merchant;
+mod message_log;
 mod player;
+mod quest;
+mod save;
+mod script;
 mod serialize;
+mod theme;
 mod tiles;
+mod travel;
 mod ui;
 mod vector;
 
+use dialogue::Dialogues;
+use flags::Flags;
 use game::{EngineParams, Game};
-use map::Map;
+use loot::LootTables;
+use map::{
+    generator::{self, GeneratorParams, Layout},
+    registry::{self, TileRegistry},
+    Map,
+};
 use player::Player;
+use quest::{Journal, Quests};
+use script::Scripts;
 use serialize::Serialize;
+use std::{env, fs, io, process};
+use theme::Theme;
+
+const MAP_FILE: &str = "data/Maps/Test.map";
+const GENERATED_MAP_WIDTH: usize = 60;
+const GENERATED_MAP_HEIGHT: usize = 34;
 
 fn main() {
-    let map = Map::load_from_file("data/Maps/Test.map").expect("Map loading failed!");
+    // Data files are set before anything reads them, e.g. maps check dialogues of NPCs.
+    registry::init(load_or_exit(
+        registry::TILES_FILE,
+        TileRegistry::load_from_file,
+    ));
+    loot::init(load_or_exit(loot::LOOT_FILE, LootTables::load_from_file));
+    dialogue::init(load_or_exit(
+        dialogue::DIALOGUES_FILE,
+        Dialogues::load_from_file,
+    ));
+    quest::init(load_or_exit(quest::QUESTS_FILE, Quests::load_from_file));
+    script::init(load_or_exit(script::SCRIPTS_FILE, Scripts::load_from_file));
+
+    let args: Vec<String> = env::args().skip(1).collect();
+    match args.as_slice() {
+        [flag, file_name] if flag == "--check" => {
+            let valid = check_map(file_name);
+            process::exit(if valid { 0 } else { 1 });
+        }
+        [flag, layout, seed, file_name] if flag == "--generate" => {
+            let generated = generate_map(layout, seed, file_name);
+            process::exit(if generated { 0 } else { 1 });
+        }
+        _ => (),
+    }
+
+    // Remaining arguments: [--theme <name>] [map file]
+    let (theme_name, rest) = match args.as_slice() {
+        [flag, name, rest @ ..] if flag == "--theme" => (name.as_str(), rest),
+        rest => (theme::DEFAULT_THEME, rest),
+    };
+
+    let map_file = match rest {
+        [] => MAP_FILE,
+        [file_name] => file_name.as_str(),
+        _ => {
+            eprintln!("Usage: text_adv [--theme <name>] [map file]");
+            eprintln!("       text_adv --check <map file>");
+            eprintln!("       text_adv --generate <rooms|caves> <seed> <map file>");
+            process::exit(1);
+        }
+    };
+
+    let theme = match Theme::load(theme_name) {
+        Ok(theme) => theme,
+        Err(error) if theme_name == theme::DEFAULT_THEME => {
+            eprintln!("Default theme not loaded ({}), using builtin one", error);
+            Theme::default()
+        }
+        Err(error) => {
+            eprintln!("Theme '{}': {}", theme_name, error);
+            process::exit(1);
+        }
+    };
+
+    if !check_map(map_file) {
+        process::exit(1);
+    }
+
+    let map = load_or_exit(map_file, Map::load_from_file);
     let mut player = Player::new("Paweł".to_string(), 10, 10, 5, 4, 7, 6);
     player.set_position(map.player_position().unwrap());
+    // Saved games are map files with player, quests and flags sections.
+    let mut journal = Journal::new();
+    let mut flags = Flags::new();
+    if let Err(error) = save::load_player(map_file, &mut player)
+        .and_then(|()| save::load_journal(map_file, &mut journal))
+        .and_then(|()| save::load_flags(map_file, &mut flags))
+    {
+        eprintln!("{}: {}", map_file, error);
+        process::exit(1);
+    }
 
-    let engine_params = EngineParams::new(80, 40, 10);
+    let engine_params = EngineParams::new(10);
 
-    let mut game = Game::new(engine_params, player, map);
+    let mut game = Game::new(engine_params, player, map, theme, journal, flags);
     while game.is_running() {
         game.handle_input();
         game.render();
     }
 }
+
+/// Loads file with given function or exits after printing error.
+fn load_or_exit<T>(file_name: &str, load: fn(&str) -> io::Result<T>) -> T {
+    load(file_name).unwrap_or_else(|error| {
+        eprintln!("{}: {}", file_name, error);
+        process::exit(1)
+    })
+}
+
+/// Validates map file and prints all problems found in it. Returns true if map is valid.
+fn check_map(file_name: &str) -> bool {
+    let raw_data = match fs::read_to_string(file_name) {
+        Ok(data) => data,
+        Err(error) => {
+            eprintln!("{}: {}", file_name, error);
+            return false;
+        }
+    };
+
+    match map::validation::validate(&raw_data) {
+        Ok(()) => true,
+        Err(diagnostics) => {
+            for diagnostic in diagnostics.iter() {
+                eprintln!("{}:{}", file_name, diagnostic);
+            }
+            false
+        }
+    }
+}
+
+/// Generates map with given layout ("rooms" or "caves") and seed and saves it to file.
+fn generate_map(layout: &str, seed: &str, file_name: &str) -> bool {
+    let layout = match layout {
+        "rooms" => Layout::rooms(),
+        "caves" => Layout::caves(),
+        _ => {
+            eprintln!("Unknown layout '{}', use 'rooms' or 'caves'", layout);
+            return false;
+        }
+    };
+
+    let seed = match seed.parse() {
+        Ok(seed) => seed,
+        Err(_) => {
+            eprintln!("Seed '{}' is not a number", seed);
+            return false;
+        }
+    };
+
+    let params = GeneratorParams::new(GENERATED_MAP_WIDTH, GENERATED_MAP_HEIGHT, seed, layout);
+    let map = generator::generate(&params);
+    if let Err(error) = map.save_to_file(file_name) {
+        eprintln!("{}: {}", file_name, error);
+        return false;
+    }
+
+    true
+}
@@ -1,31 +1,49 @@
-use crate::{serialize::Serialize, tiles, vector::Vec2};
-use console_engine::{pixel, screen::Screen};
+pub mod generator;
+pub mod pathfinding;
+pub mod registry;
+pub mod trap;
+pub mod validation;
+
+use crate::{
+    container::Container,
+    dialogue::{Effect, Npc},
+    item::Item,
+    merchant::Merchant,
+    script::{self, ScriptHook},
+    serialize::Serialize,
+    theme::Theme,
+    vector::Vec2,
+};
+use console_engine::screen::Screen;
+use registry::{key, Hook};
 use std::{
+    collections::{HashMap, HashSet},
+    fmt,
     fs::{self, File},
-    io::{self, BufReader, BufWriter, Write},
+    io::{self, BufReader, BufWriter, ErrorKind, Write},
 };
+use trap::TrapKind;
 
 const DETAILS_SECTION: &str = "[details]";
-const MULTI_SHAPE_TILES: [MapTile; 2] = [MapTile::Wall, MapTile::Road];
-
-enum MultiShapeTile {
-    Single,
-    HorizontalLeft,
-    HorizontalRight,
-    Horizontal,
-    VerticalTop,
-    VerticalBottom,
-    Vertical,
-    TopLeft,
-    TopCenter,
-    TopRight,
-    BottomLeft,
-    BottomCenter,
-    BottomRight,
-    CenterLeft,
-    CenterRight,
-    Cross,
-    Invalid(Vec2),
+const SIGNS_SECTION: &str = "[signs]";
+const CONTAINERS_SECTION: &str = "[containers]";
+const MERCHANTS_SECTION: &str = "[merchants]";
+const NPCS_SECTION: &str = "[npcs]";
+const TRIGGERS_SECTION: &str = "[triggers]";
+const SCRIPTS_SECTION: &str = "[scripts]";
+const TRAPS_SECTION: &str = "[traps]";
+
+/// Entry of map file section, which couldn't be parsed.
+#[derive(Debug, PartialEq)]
+struct SectionError {
+    line: usize,
+    message: String,
+}
+
+impl fmt::Display for SectionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
 }
 
 #[derive(Debug, PartialEq)]
@@ -35,19 +53,140 @@ pub enum MapTile {
     Grass,
     Wall,
     Road,
-    Door { locked: bool },
-    Window { locked: bool },
+    Door {
+        locked: bool,
+    },
+    Window {
+        locked: bool,
+    },
+    Water {
+        deep: bool,
+    },
+    Tree,
+    Bush,
+    StairsUp,
+    StairsDown,
+    Chest,
+    Trap {
+        kind: TrapKind,
+        hidden: bool,
+    },
+    Lava,
+    Signpost,
+    /// Tile defined only in tiles file, without any special behaviour in game.
+    Custom(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Passability {
+    Open,
+    /// Can be crossed only by swimming or by boat.
+    Water,
+    Blocked,
+}
+
+/// Abilities which let player move through some of the tiles.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Mobility {
+    pub swim: bool,
+    pub boat: bool,
+}
+
+impl Mobility {
+    pub fn can_pass(&self, passability: Passability) -> bool {
+        match passability {
+            Passability::Open => true,
+            Passability::Water => self.swim || self.boat,
+            Passability::Blocked => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TileProperties {
+    pub passability: Passability,
+    pub blocks_sight: bool,
+    /// How expensive is walking through tile, used to find best path.
+    pub movement_cost: u32,
+    /// Damage taken by player every time tile is entered.
+    pub damage: u8,
 }
 
 impl MapTile {
-    pub fn is_multishape(&self) -> bool {
-        for shape in MULTI_SHAPE_TILES.iter() {
-            if self == shape {
-                return true;
-            }
+    /// Creates tile from its registry key. Keys without special behaviour give custom tiles.
+    pub fn from_key(tile_key: &str) -> Self {
+        match tile_key {
+            key::EMPTY => MapTile::Empty,
+            key::PLAYER => MapTile::Player,
+            key::GRASS => MapTile::Grass,
+            key::WALL => MapTile::Wall,
+            key::ROAD => MapTile::Road,
+            key::DOOR_LOCKED => MapTile::Door { locked: true },
+            key::DOOR_UNLOCKED => MapTile::Door { locked: false },
+            key::WINDOW_LOCKED => MapTile::Window { locked: true },
+            key::WINDOW_UNLOCKED => MapTile::Window { locked: false },
+            key::WATER_DEEP => MapTile::Water { deep: true },
+            key::WATER_SHALLOW => MapTile::Water { deep: false },
+            key::TREE => MapTile::Tree,
+            key::BUSH => MapTile::Bush,
+            key::STAIRS_UP => MapTile::StairsUp,
+            key::STAIRS_DOWN => MapTile::StairsDown,
+            key::CHEST => MapTile::Chest,
+            key::LAVA => MapTile::Lava,
+            key::SIGNPOST => MapTile::Signpost,
+            _ => match TrapKind::from_key(tile_key) {
+                Some(kind) => MapTile::Trap { kind, hidden: true },
+                None => MapTile::Custom(tile_key.to_string()),
+            },
         }
+    }
+
+    /// Key of the tile definition in registry.
+    pub fn key(&self) -> &str {
+        match self {
+            MapTile::Empty => key::EMPTY,
+            MapTile::Player => key::PLAYER,
+            MapTile::Grass => key::GRASS,
+            MapTile::Wall => key::WALL,
+            MapTile::Road => key::ROAD,
+            MapTile::Door { locked: true } => key::DOOR_LOCKED,
+            MapTile::Door { locked: false } => key::DOOR_UNLOCKED,
+            MapTile::Window { locked: true } => key::WINDOW_LOCKED,
+            MapTile::Window { locked: false } => key::WINDOW_UNLOCKED,
+            MapTile::Water { deep: true } => key::WATER_DEEP,
+            MapTile::Water { deep: false } => key::WATER_SHALLOW,
+            MapTile::Tree => key::TREE,
+            MapTile::Bush => key::BUSH,
+            MapTile::StairsUp => key::STAIRS_UP,
+            MapTile::StairsDown => key::STAIRS_DOWN,
+            MapTile::Chest => key::CHEST,
+            MapTile::Trap { kind, .. } => kind.key(),
+            MapTile::Lava => key::LAVA,
+            MapTile::Signpost => key::SIGNPOST,
+            MapTile::Custom(tile_key) => tile_key,
+        }
+    }
+
+    /// Key of the style used to draw this tile.
+    pub fn style_key(&self) -> &str {
+        match self {
+            // Hidden trap looks like an empty floor.
+            MapTile::Trap { hidden: true, .. } => key::EMPTY,
+            _ => self.key(),
+        }
+    }
 
-        false
+    pub fn properties(&self) -> TileProperties {
+        registry::get().tile(self.key()).properties
+    }
+
+    pub fn hook(&self) -> Option<Hook> {
+        registry::get().tile(self.key()).hook
+    }
+
+    /// Checks if tile can be entered without any special abilities.
+    pub fn is_passable(&self) -> bool {
+        self.properties().passability == Passability::Open
     }
 }
 
@@ -57,7 +196,17 @@ pub struct Map {
     height: usize,
     tiles: Vec<MapTile>,
     details: String,
+    signs: HashMap<Vec2, String>,
+    containers: HashMap<Vec2, Container>,
+    merchants: HashMap<Vec2, Merchant>,
+    npcs: HashMap<Vec2, Npc>,
+    /// Effects applied every time player enters tile.
+    triggers: HashMap<Vec2, Vec<Effect>>,
+    /// Names of scripts attached to tiles.
+    scripts: HashMap<Vec2, Vec<(ScriptHook, String)>>,
     starting_position: Option<Vec2>,
+    /// Indexes of tiles which have to be drawn again.
+    dirty: HashSet<usize>,
 }
 
 impl Map {
@@ -65,6 +214,14 @@ impl Map {
         &self.screen
     }
 
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
     pub fn get_tile(&self, x: usize, y: usize) -> Option<&MapTile> {
         if x < self.width && y < self.height {
             let index = y * self.width + x;
@@ -74,11 +231,26 @@ impl Map {
         }
     }
 
+    /// Changes tile and marks it, together with its neighbours, to be drawn again.
     pub fn set_tile(&mut self, x: usize, y: usize, tile: MapTile) {
         assert!(x < self.width && y < self.height);
 
         let index = y * self.width + x;
         self.tiles[index] = tile;
+
+        // Neighbours have to be redrawn too, because connected glyphs depend on this tile.
+        let position = Vec2::new(x as i32, y as i32);
+        self.dirty.insert(index);
+        for direction in [Vec2::UP, Vec2::DOWN, Vec2::LEFT, Vec2::RIGHT].iter() {
+            let neighbour = position + *direction;
+            if self.contains(neighbour) {
+                self.dirty.insert(neighbour.to_index(self.width));
+            }
+        }
+    }
+
+    pub fn contains(&self, pos: Vec2) -> bool {
+        pos.x >= 0 && pos.y >= 0 && (pos.x as usize) < self.width && (pos.y as usize) < self.height
     }
 
     pub fn details(&self) -> &str {
@@ -89,208 +261,533 @@ impl Map {
         self.starting_position
     }
 
-    pub fn can_move(&self, pos: Vec2) -> bool {
-        match self.tiles[pos.to_index(self.width)] {
-            MapTile::Wall | MapTile::Window { locked: true } | MapTile::Door { locked: true } => {
-                false
+    /// Positions of hidden traps in given distance from `pos`, counted in both axes.
+    pub fn hidden_traps_near(&self, pos: Vec2, radius: i32) -> Vec<Vec2> {
+        let mut traps = Vec::new();
+        for y in pos.y - radius..=pos.y + radius {
+            for x in pos.x - radius..=pos.x + radius {
+                let near = Vec2::new(x, y);
+                if let Some(MapTile::Trap { hidden: true, .. }) = self.tile_at(near) {
+                    traps.push(near);
+                }
             }
-            _ => true,
+        }
+        traps
+    }
+
+    /// Makes trap at given position visible. Other tiles are left unchanged.
+    pub fn reveal_trap(&mut self, pos: Vec2) {
+        if let Some(MapTile::Trap { kind, hidden: true }) = self.tile_at(pos) {
+            let kind = *kind;
+            self.set_tile(
+                pos.x as usize,
+                pos.y as usize,
+                MapTile::Trap {
+                    kind,
+                    hidden: false,
+                },
+            );
+        }
+    }
+
+    /// Text written on signpost at given position.
+    pub fn sign_text(&self, pos: Vec2) -> Option<&str> {
+        self.signs.get(&pos).map(|text| text.as_str())
+    }
+
+    pub fn container(&self, pos: Vec2) -> Option<&Container> {
+        self.containers.get(&pos)
+    }
+
+    pub fn container_mut(&mut self, pos: Vec2) -> Option<&mut Container> {
+        self.containers.get_mut(&pos)
+    }
+
+    /// Puts items on the floor. They are added to container already lying there.
+    pub fn drop_items(&mut self, pos: Vec2, items: Vec<Item>) {
+        let container = self
+            .containers
+            .entry(pos)
+            .or_insert_with(|| Container::new(vec![], false));
+        container.add_items(items);
+    }
+
+    pub fn remove_container(&mut self, pos: Vec2) {
+        self.containers.remove(&pos);
+    }
+
+    /// Positions of containers placed on tiles which can be walked on, like enemy drops.
+    pub fn pickups(&self) -> Vec<Vec2> {
+        self.containers
+            .keys()
+            .filter(|pos| self.tile_at(**pos).is_some_and(|tile| tile.is_passable()))
+            .copied()
+            .collect()
+    }
+
+    /// Merchant standing at given position.
+    pub fn merchant(&self, pos: Vec2) -> Option<&Merchant> {
+        self.merchants.get(&pos)
+    }
+
+    pub fn merchant_mut(&mut self, pos: Vec2) -> Option<&mut Merchant> {
+        self.merchants.get_mut(&pos)
+    }
+
+    pub fn npc(&self, pos: Vec2) -> Option<&Npc> {
+        self.npcs.get(&pos)
+    }
+
+    /// Positions of NPCs and merchants.
+    pub fn npc_positions(&self) -> Vec<Vec2> {
+        let merchants = self
+            .merchants
+            .keys()
+            .filter(|pos| !self.npcs.contains_key(pos));
+        self.npcs.keys().chain(merchants).copied().collect()
+    }
+
+    /// Positions of chests and items lying on the floor.
+    pub fn container_positions(&self) -> Vec<Vec2> {
+        self.containers.keys().copied().collect()
+    }
+
+    /// Positions of tiles with given key.
+    pub fn tile_positions(&self, tile_key: &str) -> Vec<Vec2> {
+        self.tiles
+            .iter()
+            .enumerate()
+            .filter(|(_, tile)| tile.key() == tile_key)
+            .map(|(idx, _)| Vec2::from_index(idx, self.width))
+            .collect()
+    }
+
+    pub fn triggers(&self, pos: Vec2) -> &[Effect] {
+        self.triggers
+            .get(&pos)
+            .map_or(&[], |effects| effects.as_slice())
+    }
+
+    /// Names of scripts attached to tile, which are run at given hook.
+    pub fn scripts(&self, pos: Vec2, hook: ScriptHook) -> Vec<String> {
+        self.scripts.get(&pos).map_or(Vec::new(), |scripts| {
+            scripts
+                .iter()
+                .filter(|(script_hook, _)| *script_hook == hook)
+                .map(|(_, name)| name.clone())
+                .collect()
+        })
+    }
+
+    /// Names of scripts run when player bumps into tile. Tiles which can be entered are
+    /// walked onto instead, e.g. secret passage once it was opened.
+    pub fn use_scripts(&self, pos: Vec2, mobility: Mobility) -> Vec<String> {
+        if self.can_move_with(pos, mobility) {
+            Vec::new()
+        } else {
+            self.scripts(pos, ScriptHook::Use)
+        }
+    }
+
+    /// Lets time pass for everyone on map, so merchants can restock.
+    pub fn pass_turn(&mut self) {
+        let mut rng = rand::thread_rng();
+        for merchant in self.merchants.values_mut() {
+            merchant.pass_turn(&mut rng);
         }
     }
 
-    fn render_map(&mut self) {
+    /// Checks if tile can be entered by player with given abilities.
+    pub fn can_move_with(&self, pos: Vec2, mobility: Mobility) -> bool {
+        self.tile_at(pos)
+            .is_some_and(|tile| mobility.can_pass(tile.properties().passability))
+    }
+
+    pub fn tile_at(&self, pos: Vec2) -> Option<&MapTile> {
+        if self.contains(pos) {
+            Some(&self.tiles[pos.to_index(self.width)])
+        } else {
+            None
+        }
+    }
+
+    pub fn movement_cost(&self, pos: Vec2) -> u32 {
+        self.tile_at(pos)
+            .map_or(1, |tile| tile.properties().movement_cost)
+    }
+
+    /// Creates map from tiles stored row by row. Tiles vector has to contain `width * height` elements.
+    pub fn from_tiles(
+        width: usize,
+        height: usize,
+        tiles: Vec<MapTile>,
+        starting_position: Option<Vec2>,
+        details: String,
+    ) -> Self {
+        assert_eq!(tiles.len(), width * height);
+
+        let mut map = Self {
+            screen: Screen::new(width as u32, height as u32),
+            width,
+            height,
+            tiles,
+            details,
+            signs: HashMap::new(),
+            containers: HashMap::new(),
+            merchants: HashMap::new(),
+            npcs: HashMap::new(),
+            triggers: HashMap::new(),
+            scripts: HashMap::new(),
+            starting_position,
+            dirty: HashSet::new(),
+        };
+        map.render_map(&Theme::default());
+        map
+    }
+
+    /// Returns map in the same text format, which is used by map files.
+    pub fn to_map_data(&self) -> String {
+        let mut data = String::new();
+
         for (idx, tile) in self.tiles.iter().enumerate() {
-            let current_position = Vec2::from_index(idx, self.width);
-            let ch = if tile.is_multishape() {
-                // Handle multishape tiles. This code is very non optimal, but it works.
-                // I will optimize it if it will be too slow or I will have enough time.
-                // TODO: Optimize this code to use just indexes in vector.
-
-                let beginning_row = idx < self.width;
-                let ending_row = current_position.y as usize == self.height - 1;
-                let beginning_column = idx % self.width == 0;
-                let ending_column = current_position.x as usize == self.width - 1;
-
-                let mut check_directions = vec![Vec2::UP, Vec2::DOWN, Vec2::LEFT, Vec2::RIGHT];
-                if beginning_row {
-                    check_directions.retain(|elem| *elem != Vec2::UP);
-                } else if ending_row {
-                    check_directions.retain(|elem| *elem != Vec2::DOWN);
-                }
-                if beginning_column {
-                    check_directions.retain(|elem| *elem != Vec2::LEFT);
-                } else if ending_column {
-                    check_directions.retain(|elem| *elem != Vec2::RIGHT);
-                }
+            let position = Vec2::from_index(idx, self.width);
+            if self.starting_position == Some(position) {
+                data.push(Self::char_from_tile(&MapTile::Player));
+            } else {
+                data.push(Self::char_from_tile(tile));
+            }
 
-                let mut shape = MultiShapeTile::Single;
-                for direction in check_directions.iter() {
-                    let checked_index = (current_position + *direction).to_index(self.width);
-                    if *tile != self.tiles[checked_index] {
-                        continue;
-                    }
-
-                    shape = match shape {
-                        MultiShapeTile::Single => match *direction {
-                            Vec2::UP => MultiShapeTile::VerticalTop,
-                            Vec2::DOWN => MultiShapeTile::VerticalBottom,
-                            Vec2::LEFT => MultiShapeTile::HorizontalLeft,
-                            Vec2::RIGHT => MultiShapeTile::HorizontalRight,
-                            _ => MultiShapeTile::Invalid(*direction),
-                        },
-                        MultiShapeTile::HorizontalLeft => match *direction {
-                            Vec2::UP => MultiShapeTile::BottomRight,
-                            Vec2::RIGHT => MultiShapeTile::Horizontal,
-                            Vec2::DOWN => MultiShapeTile::TopRight,
-                            _ => MultiShapeTile::Invalid(*direction),
-                        },
-                        MultiShapeTile::HorizontalRight => match *direction {
-                            Vec2::LEFT => MultiShapeTile::Horizontal,
-                            Vec2::UP => MultiShapeTile::BottomLeft,
-                            Vec2::DOWN => MultiShapeTile::TopLeft,
-                            _ => MultiShapeTile::Invalid(*direction),
-                        },
-                        MultiShapeTile::Horizontal => match *direction {
-                            Vec2::UP => MultiShapeTile::BottomCenter,
-                            Vec2::DOWN => MultiShapeTile::TopCenter,
-                            _ => MultiShapeTile::Invalid(*direction),
-                        },
-                        MultiShapeTile::VerticalTop => match *direction {
-                            Vec2::DOWN => MultiShapeTile::Vertical,
-                            Vec2::LEFT => MultiShapeTile::BottomRight,
-                            Vec2::RIGHT => MultiShapeTile::BottomLeft,
-                            _ => MultiShapeTile::Invalid(*direction),
-                        },
-                        MultiShapeTile::VerticalBottom => match *direction {
-                            Vec2::UP => MultiShapeTile::Vertical,
-                            Vec2::LEFT => MultiShapeTile::TopRight,
-                            Vec2::RIGHT => MultiShapeTile::TopLeft,
-                            _ => MultiShapeTile::Invalid(*direction),
-                        },
-                        MultiShapeTile::Vertical => match *direction {
-                            Vec2::LEFT => MultiShapeTile::CenterRight,
-                            Vec2::RIGHT => MultiShapeTile::CenterLeft,
-                            _ => MultiShapeTile::Invalid(*direction),
-                        },
-                        MultiShapeTile::TopLeft => match *direction {
-                            Vec2::UP => MultiShapeTile::CenterLeft,
-                            Vec2::LEFT => MultiShapeTile::TopCenter,
-                            _ => MultiShapeTile::Invalid(*direction),
-                        },
-                        MultiShapeTile::TopCenter => match *direction {
-                            Vec2::UP => MultiShapeTile::Cross,
-                            _ => MultiShapeTile::Invalid(*direction),
-                        },
-                        MultiShapeTile::TopRight => match *direction {
-                            Vec2::UP => MultiShapeTile::CenterRight,
-                            Vec2::RIGHT => MultiShapeTile::TopCenter,
-                            _ => MultiShapeTile::Invalid(*direction),
-                        },
-                        MultiShapeTile::BottomLeft => match *direction {
-                            Vec2::DOWN => MultiShapeTile::CenterLeft,
-                            Vec2::LEFT => MultiShapeTile::BottomCenter,
-                            _ => MultiShapeTile::Invalid(*direction),
-                        },
-                        MultiShapeTile::BottomCenter => match *direction {
-                            Vec2::DOWN => MultiShapeTile::Cross,
-                            _ => MultiShapeTile::Invalid(*direction),
-                        },
-                        MultiShapeTile::BottomRight => match *direction {
-                            Vec2::DOWN => MultiShapeTile::CenterRight,
-                            Vec2::RIGHT => MultiShapeTile::BottomCenter,
-                            _ => MultiShapeTile::Invalid(*direction),
-                        },
-                        MultiShapeTile::CenterLeft => match *direction {
-                            Vec2::LEFT => MultiShapeTile::Cross,
-                            _ => MultiShapeTile::Invalid(*direction),
-                        },
-                        MultiShapeTile::CenterRight => match *direction {
-                            Vec2::RIGHT => MultiShapeTile::Cross,
-                            _ => MultiShapeTile::Invalid(*direction),
-                        },
-                        _ => MultiShapeTile::Invalid(*direction),
-                    };
-                }
+            if position.x as usize == self.width - 1 {
+                data.push('\n');
+            }
+        }
+
+        data.push('\n');
+        data.push_str(DETAILS_SECTION);
+        data.push('\n');
+        data.push_str(&self.details);
+
+        if !self.signs.is_empty() {
+            let mut signs: Vec<(&Vec2, &String)> = self.signs.iter().collect();
+            signs.sort_by_key(|(pos, _)| (pos.y, pos.x));
+
+            data.push('\n');
+            data.push_str(SIGNS_SECTION);
+            data.push('\n');
+            for (pos, text) in signs {
+                data.push_str(&format!("{},{} = {}\n", pos.x, pos.y, text));
+            }
+        }
+
+        if !self.containers.is_empty() {
+            let mut containers: Vec<(&Vec2, &Container)> = self.containers.iter().collect();
+            containers.sort_by_key(|(pos, _)| (pos.y, pos.x));
+
+            data.push('\n');
+            data.push_str(CONTAINERS_SECTION);
+            data.push('\n');
+            for (pos, container) in containers {
+                data.push_str(&format!("{},{} = {}\n", pos.x, pos.y, container.to_code()));
+            }
+        }
+
+        if !self.merchants.is_empty() {
+            let mut merchants: Vec<(&Vec2, &Merchant)> = self.merchants.iter().collect();
+            merchants.sort_by_key(|(pos, _)| (pos.y, pos.x));
+
+            data.push('\n');
+            data.push_str(MERCHANTS_SECTION);
+            data.push('\n');
+            for (pos, merchant) in merchants {
+                data.push_str(&format!("{},{} = {}\n", pos.x, pos.y, merchant.to_code()));
+            }
+        }
+
+        if !self.npcs.is_empty() {
+            let mut npcs: Vec<(&Vec2, &Npc)> = self.npcs.iter().collect();
+            npcs.sort_by_key(|(pos, _)| (pos.y, pos.x));
+
+            data.push('\n');
+            data.push_str(NPCS_SECTION);
+            data.push('\n');
+            for (pos, npc) in npcs {
+                data.push_str(&format!("{},{} = {}\n", pos.x, pos.y, npc.to_code()));
+            }
+        }
 
-                match shape {
-                    MultiShapeTile::Invalid(direction) => {
-                        panic!("Invalid direction for neighbour tile: {:?}", direction)
-                    }
-                    MultiShapeTile::Cross => tiles::border::CENTER_CENTER,
-                    MultiShapeTile::Single => tiles::border::CENTER_CENTER,
-                    MultiShapeTile::TopLeft => tiles::border::TOP_LEFT,
-                    MultiShapeTile::TopRight => tiles::border::TOP_RIGHT,
-                    MultiShapeTile::TopCenter => tiles::border::TOP_CENTER,
-                    MultiShapeTile::BottomLeft => tiles::border::BOTTOM_LEFT,
-                    MultiShapeTile::BottomRight => tiles::border::BOTTOM_RIGHT,
-                    MultiShapeTile::BottomCenter => tiles::border::BOTTOM_CENTER,
-                    MultiShapeTile::CenterLeft => tiles::border::CENTER_LEFT,
-                    MultiShapeTile::CenterRight => tiles::border::CENTER_RIGHT,
-                    MultiShapeTile::HorizontalLeft
-                    | MultiShapeTile::HorizontalRight
-                    | MultiShapeTile::Horizontal => tiles::border::HORIZONTAL,
-                    MultiShapeTile::VerticalTop
-                    | MultiShapeTile::VerticalBottom
-                    | MultiShapeTile::Vertical => tiles::border::VERTICAL,
+        if !self.triggers.is_empty() {
+            let mut triggers: Vec<(&Vec2, &Vec<Effect>)> = self.triggers.iter().collect();
+            triggers.sort_by_key(|(pos, _)| (pos.y, pos.x));
+
+            data.push('\n');
+            data.push_str(TRIGGERS_SECTION);
+            data.push('\n');
+            for (pos, effects) in triggers {
+                for effect in effects {
+                    data.push_str(&format!("{},{} = {}\n", pos.x, pos.y, effect));
                 }
-            } else {
-                match *tile {
-                    MapTile::Empty => tiles::EMPTY,
-                    MapTile::Door { locked } => {
-                        if locked {
-                            tiles::door::LOCKED
-                        } else {
-                            tiles::door::UNLOCKED
-                        }
-                    }
-                    MapTile::Grass => tiles::GRASS,
-                    MapTile::Window { locked } => {
-                        if locked {
-                            tiles::window::LOCKED
-                        } else {
-                            tiles::window::UNLOCKED
-                        }
-                    }
-                    MapTile::Player => tiles::PLAYER,
-                    _ => panic!("Tile {:?} is not implemented or multishape", tile),
+            }
+        }
+
+        if !self.scripts.is_empty() {
+            let mut scripts: Vec<(&Vec2, &Vec<(ScriptHook, String)>)> =
+                self.scripts.iter().collect();
+            scripts.sort_by_key(|(pos, _)| (pos.y, pos.x));
+
+            data.push('\n');
+            data.push_str(SCRIPTS_SECTION);
+            data.push('\n');
+            for (pos, scripts) in scripts {
+                for (hook, name) in scripts {
+                    data.push_str(&format!("{},{} = {} {}\n", pos.x, pos.y, hook.name(), name));
                 }
-            };
+            }
+        }
 
-            // Render ch at proper position
-            let pixel = pixel::pxl(ch);
-            self.screen
-                .set_pxl(current_position.x, current_position.y, pixel);
+        let revealed: Vec<Vec2> = self
+            .tiles
+            .iter()
+            .enumerate()
+            .filter(|(_, tile)| matches!(tile, MapTile::Trap { hidden: false, .. }))
+            .map(|(idx, _)| Vec2::from_index(idx, self.width))
+            .collect();
+        if !revealed.is_empty() {
+            data.push('\n');
+            data.push_str(TRAPS_SECTION);
+            data.push('\n');
+            for pos in revealed {
+                data.push_str(&format!("{},{} = revealed\n", pos.x, pos.y));
+            }
         }
+
+        data
     }
 
-    fn split_for_map_and_details(raw_data: &str) -> (String, String) {
-        let map: String = raw_data
+    /// Draws all tiles on map screen with glyphs and colors taken from theme.
+    pub fn render_map(&mut self, theme: &Theme) {
+        for idx in 0..self.tiles.len() {
+            self.render_tile(idx, theme);
+        }
+        self.dirty.clear();
+    }
+
+    /// Draws again only tiles changed with `set_tile` since last render.
+    pub fn render_changes(&mut self, theme: &Theme) {
+        let dirty: Vec<usize> = self.dirty.drain().collect();
+        for idx in dirty {
+            self.render_tile(idx, theme);
+        }
+    }
+
+    fn render_tile(&mut self, idx: usize, theme: &Theme) {
+        let current_position = Vec2::from_index(idx, self.width);
+        let key = self.tiles[idx].style_key();
+        let style = theme.style(key);
+
+        let ch = match theme.connection(key) {
+            Some(connection) => {
+                // Connected tiles are drawn with glyph joining them with their neighbours.
+                let connects = |direction: Vec2| {
+                    let neighbour = current_position + direction;
+                    self.contains(neighbour)
+                        && connection
+                            .connects_to(self.tiles[neighbour.to_index(self.width)].style_key())
+                };
+
+                connection.glyphs.connecting_glyph(
+                    connects(Vec2::UP),
+                    connects(Vec2::DOWN),
+                    connects(Vec2::LEFT),
+                    connects(Vec2::RIGHT),
+                )
+            }
+            None => style.glyph,
+        };
+
+        // Render ch at proper position
+        let pixel = style.pixel_with(ch);
+        self.screen
+            .set_pxl(current_position.x, current_position.y, pixel);
+    }
+
+    /// Returns map rows together with their line numbers (starting from 1) in raw file data.
+    /// Map rows end at the first section header.
+    fn map_lines(raw_data: &str) -> impl Iterator<Item = (usize, &str)> {
+        raw_data
             .lines()
-            .filter(|line| !line.is_empty() && !line.trim_start().starts_with(";"))
-            .take_while(|line| line.trim() != DETAILS_SECTION)
-            .map(|line| format!("{}\n", line))
-            .collect();
+            .enumerate()
+            .map(|(idx, line)| (idx + 1, line))
+            .filter(|(_, line)| !line.is_empty() && !line.trim_start().starts_with(';'))
+            .take_while(|(_, line)| !line.trim_start().starts_with('['))
+    }
+
+    /// Returns lines of given section without comments and empty lines.
+    pub fn section_lines<'a>(raw_data: &'a str, section: &'a str) -> impl Iterator<Item = &'a str> {
+        Self::numbered_section_lines(raw_data, section).map(|(_, line)| line)
+    }
 
-        let map_details: String = raw_data
+    /// Returns lines of given section together with their line numbers (starting from 1).
+    fn numbered_section_lines<'a>(
+        raw_data: &'a str,
+        section: &'a str,
+    ) -> impl Iterator<Item = (usize, &'a str)> {
+        raw_data
             .lines()
-            .skip_while(|line| line.trim() != DETAILS_SECTION)
+            .enumerate()
+            .map(|(idx, line)| (idx + 1, line))
+            .skip_while(move |(_, line)| line.trim() != section)
             .skip(1)
-            .filter(|line| !line.is_empty() && !line.trim_start().starts_with(";"))
+            .take_while(|(_, line)| !line.trim_start().starts_with('['))
+            .filter(|(_, line)| !line.is_empty() && !line.trim_start().starts_with(';'))
+    }
+
+    fn split_for_map_and_details(raw_data: &str) -> (String, String) {
+        let map: String = Self::map_lines(raw_data)
+            .map(|(_, line)| format!("{}\n", line))
+            .collect();
+
+        let map_details: String = Self::section_lines(raw_data, DETAILS_SECTION)
             .map(|line| format!("{}\n", line))
             .collect();
 
         (map, map_details)
     }
 
-    fn parse_data(map_data: String, details: String) -> Self {
+    /// Parses signs section. Every line has format: `x,y = text`.
+    fn parse_signs(raw_data: &str) -> Result<HashMap<Vec2, String>, SectionError> {
+        let signs =
+            Self::parse_position_section(raw_data, SIGNS_SECTION, |text| Ok(text.to_string()))?;
+        Ok(signs.into_iter().collect())
+    }
+
+    /// Parses containers section. Every line has format: `x,y = contents`, see
+    /// `Container::parse`. Loot tables are rolled every time map is loaded.
+    fn parse_containers(raw_data: &str) -> Result<HashMap<Vec2, Container>, SectionError> {
+        let mut rng = rand::thread_rng();
+        let containers = Self::parse_position_section(raw_data, CONTAINERS_SECTION, |text| {
+            Container::parse(text, &mut rng)
+        })?;
+        Ok(containers.into_iter().collect())
+    }
+
+    /// Parses merchants section. Every line has format: `x,y = @table restock_turns`, see
+    /// `Merchant::parse`.
+    fn parse_merchants(raw_data: &str) -> Result<HashMap<Vec2, Merchant>, SectionError> {
+        let mut rng = rand::thread_rng();
+        let merchants = Self::parse_position_section(raw_data, MERCHANTS_SECTION, |text| {
+            Merchant::parse(text, &mut rng)
+        })?;
+        Ok(merchants.into_iter().collect())
+    }
+
+    /// Parses NPCs section. Every line has format: `x,y = @dialogue name`. NPC placed on
+    /// merchant tile is talked to before trading.
+    fn parse_npcs(raw_data: &str) -> Result<HashMap<Vec2, Npc>, SectionError> {
+        let npcs = Self::parse_position_section(raw_data, NPCS_SECTION, Npc::parse)?;
+        Ok(npcs.into_iter().collect())
+    }
+
+    /// Parses triggers section. Every line has format: `x,y = effect`, with effects written
+    /// the same way as in dialogues. Tile can have many triggers.
+    fn parse_triggers(raw_data: &str) -> Result<HashMap<Vec2, Vec<Effect>>, SectionError> {
+        let mut triggers: HashMap<Vec2, Vec<Effect>> = HashMap::new();
+        for (pos, effect) in
+            Self::parse_position_section(raw_data, TRIGGERS_SECTION, Effect::parse)?
+        {
+            triggers.entry(pos).or_default().push(effect);
+        }
+        Ok(triggers)
+    }
+
+    /// Parses scripts section. Every line has format: `x,y = hook script`, where hook is
+    /// `on_enter`, `on_use` or `on_talk`. Scripts have to exist in scripts file.
+    fn parse_scripts(
+        raw_data: &str,
+    ) -> Result<HashMap<Vec2, Vec<(ScriptHook, String)>>, SectionError> {
+        let mut scripts: HashMap<Vec2, Vec<(ScriptHook, String)>> = HashMap::new();
+        let parsed = Self::parse_position_section(raw_data, SCRIPTS_SECTION, |text| {
+            let (hook, name) = text.split_once(' ').ok_or("expected hook and script")?;
+            let hook = ScriptHook::from_name(hook).ok_or(format!("unknown hook '{}'", hook))?;
+            let name = name.trim();
+            if script::scripts().get(name).is_none() {
+                return Err(format!("unknown script '{}'", name));
+            }
+            Ok((hook, name.to_string()))
+        })?;
+        for (pos, script) in parsed {
+            scripts.entry(pos).or_default().push(script);
+        }
+        Ok(scripts)
+    }
+
+    /// Parses traps section. Every line has format: `x,y = revealed`. Traps not listed there
+    /// stay hidden.
+    fn parse_revealed_traps(raw_data: &str) -> Result<Vec<Vec2>, SectionError> {
+        let traps = Self::parse_position_section(raw_data, TRAPS_SECTION, |state| match state {
+            "revealed" => Ok(()),
+            _ => Err(format!("unknown trap state '{}'", state)),
+        })?;
+        Ok(traps.into_iter().map(|(pos, _)| pos).collect())
+    }
+
+    /// Parses every line of section in format: `x,y = text`, with text read by `parse`.
+    /// Stops at the first line, which can't be parsed.
+    fn parse_position_section<T>(
+        raw_data: &str,
+        section: &str,
+        mut parse: impl FnMut(&str) -> Result<T, String>,
+    ) -> Result<Vec<(Vec2, T)>, SectionError> {
+        Self::numbered_section_lines(raw_data, section)
+            .map(|(line, text)| {
+                let error = move |message: String| SectionError { line, message };
+                let (pos, text) = Self::parse_position_line(text).map_err(error)?;
+                Ok((pos, parse(text).map_err(error)?))
+            })
+            .collect()
+    }
+
+    /// Splits line in format: `x,y = text` into position and text.
+    fn parse_position_line(line: &str) -> Result<(Vec2, &str), String> {
+        let (coords, text) = line.split_once('=').ok_or("missing '='")?;
+        let invalid = || format!("invalid position '{}'", coords.trim());
+        let (x, y) = coords.split_once(',').ok_or_else(invalid)?;
+        let x = x.trim().parse().map_err(|_| invalid())?;
+        let y = y.trim().parse().map_err(|_| invalid())?;
+        Ok((Vec2::new(x, y), text.trim()))
+    }
+
+    /// Parses every section and returns the first error found in each of them.
+    fn section_errors(raw_data: &str) -> Vec<SectionError> {
+        vec![
+            Self::parse_signs(raw_data).err(),
+            Self::parse_containers(raw_data).err(),
+            Self::parse_merchants(raw_data).err(),
+            Self::parse_npcs(raw_data).err(),
+            Self::parse_triggers(raw_data).err(),
+            Self::parse_scripts(raw_data).err(),
+            Self::parse_revealed_traps(raw_data).err(),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+
+    /// Creates map from its rows, without any sections.
+    pub fn parse_data(map_data: String, details: String) -> Self {
         let mut tiles = Vec::new();
         let mut player_position = None;
-        let mut width = 0;
+        let width = map_data
+            .lines()
+            .map(|line| line.chars().count())
+            .max()
+            .unwrap_or(0);
         let mut height = 0;
 
         for (y, line) in map_data.lines().enumerate() {
+            let mut row_length = 0;
             for (x, ch) in line.chars().enumerate() {
-                let tile = Self::tile_from_char(ch);
+                // Unknown characters are reported by map validation, here they just become empty tiles.
+                let tile = Self::tile_from_char(ch).unwrap_or(MapTile::Empty);
 
                 if tile == MapTile::Player {
                     player_position = Some(Vec2::new(x as i32, y as i32));
@@ -298,9 +795,12 @@ impl Map {
                 } else {
                     tiles.push(tile);
                 }
-                width = width.max(x + 1);
+                row_length += 1;
             }
-            height = height.max(y + 1);
+
+            // Pad shorter rows, so every row has the same width and indexes stay valid.
+            tiles.extend((row_length..width).map(|_| MapTile::Empty));
+            height = y + 1;
         }
 
         let screen = Screen::new(width as u32, height as u32);
@@ -311,22 +811,24 @@ impl Map {
             height,
             tiles,
             details,
+            signs: HashMap::new(),
+            containers: HashMap::new(),
+            merchants: HashMap::new(),
+            npcs: HashMap::new(),
+            triggers: HashMap::new(),
+            scripts: HashMap::new(),
             starting_position: player_position,
+            dirty: HashSet::new(),
         }
     }
 
-    fn tile_from_char(ch: char) -> MapTile {
-        match ch {
-            '@' => MapTile::Player,
-            '.' => MapTile::Grass,
-            '+' => MapTile::Road,
-            '#' => MapTile::Wall,
-            'W' => MapTile::Window { locked: true },
-            'w' => MapTile::Window { locked: false },
-            'D' => MapTile::Door { locked: true },
-            'd' => MapTile::Door { locked: false },
-            _ => MapTile::Empty,
-        }
+    fn tile_from_char(ch: char) -> Option<MapTile> {
+        registry::get().key_for_char(ch).map(MapTile::from_key)
+    }
+
+    fn char_from_tile(tile: &MapTile) -> char {
+        // Only tiles read from map files exist on map, so they always have char.
+        registry::get().tile(tile.key()).map_char.unwrap_or(' ')
     }
 }
 
@@ -337,12 +839,25 @@ impl Serialize for Map {
         let (map_data, details) = Self::split_for_map_and_details(&raw_data);
 
         let mut map = Self::parse_data(map_data, details);
-        map.render_map();
+        let invalid =
+            |error: SectionError| io::Error::new(ErrorKind::InvalidData, error.to_string());
+        map.signs = Self::parse_signs(&raw_data).map_err(invalid)?;
+        map.containers = Self::parse_containers(&raw_data).map_err(invalid)?;
+        map.merchants = Self::parse_merchants(&raw_data).map_err(invalid)?;
+        map.npcs = Self::parse_npcs(&raw_data).map_err(invalid)?;
+        map.triggers = Self::parse_triggers(&raw_data).map_err(invalid)?;
+        map.scripts = Self::parse_scripts(&raw_data).map_err(invalid)?;
+        for pos in Self::parse_revealed_traps(&raw_data).map_err(invalid)? {
+            map.reveal_trap(pos);
+        }
+        map.render_map(&Theme::default());
         Ok(map)
     }
 
     fn save_to_file(&self, file_name: &str) -> io::Result<()> {
-        unimplemented!()
+        let mut writer = BufWriter::new(File::create(file_name)?);
+        writer.write_all(self.to_map_data().as_bytes())?;
+        writer.flush()
     }
 
     fn load_from_reader(reader: &mut BufReader<File>) -> Result<Map, &str> {
@@ -353,3 +868,62 @@ impl Serialize for Map {
         unimplemented!()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_map_terrain_and_signs() {
+        let map = Map::load_from_file("data/Maps/Test.map").unwrap();
+
+        assert_eq!(
+            map.sign_text(Vec2::new(1, 1)),
+            Some("Welcome to the test map!")
+        );
+        assert!(!map.can_move_with(Vec2::new(1, 1), Mobility::default()));
+        assert!(map.can_move_with(Vec2::new(12, 1), Mobility::default()));
+        assert_eq!(map.movement_cost(Vec2::new(8, 9)), 2);
+        assert!(map.details().starts_with("This is first detail."));
+
+        // Trader talks first and opens trade from dialogue.
+        let trader = Vec2::new(13, 5);
+        assert!(map.merchant(trader).is_some());
+        assert_eq!(map.npc(trader).unwrap().dialogue, "trader");
+    }
+
+    #[test]
+    fn changed_tiles_are_redrawn_with_neighbours() {
+        let data = "#####\n#@  #\n#####\n";
+        let theme = Theme::default();
+        let mut map = Map::parse_data(data.to_string(), String::new());
+        map.render_map(&theme);
+
+        map.set_tile(2, 0, MapTile::Door { locked: false });
+        map.set_tile(3, 1, MapTile::Wall);
+        map.render_changes(&theme);
+
+        let glyph = |x, y| map.screen().get_pxl(x, y).unwrap().chr;
+        assert_eq!(glyph(2, 0), 'd');
+        assert_eq!(glyph(3, 0), '┳');
+        assert_eq!(glyph(3, 1), '┣');
+        assert_eq!(glyph(4, 1), '┫');
+        assert_eq!(glyph(3, 2), '┻');
+    }
+
+    #[test]
+    fn roads_and_walls_use_own_glyphs() {
+        let data = "#####\n#@  #\n#++d#\n##+##\n";
+        let mut map = Map::parse_data(data.to_string(), String::new());
+        map.render_map(&Theme::default());
+
+        let glyph = |x, y| map.screen().get_pxl(x, y).unwrap().chr;
+        assert_eq!(glyph(0, 0), '┏');
+        assert_eq!(glyph(3, 2), 'd');
+        // Both wall and road connect with the door between them.
+        assert_eq!(glyph(4, 2), '┫');
+        assert_eq!(glyph(1, 2), '─');
+        assert_eq!(glyph(2, 2), '┬');
+        assert_eq!(glyph(2, 3), '│');
+    }
+}
@@ -0,0 +1,442 @@
+use super::{pathfinding, trap::TrapKind, Map, MapTile};
+use crate::vector::Vec2;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+const MIN_WIDTH: usize = 10;
+const MIN_HEIGHT: usize = 10;
+const ROOM_PLACEMENT_ATTEMPTS: usize = 200;
+
+pub enum Layout {
+    /// Rectangular rooms connected with roads.
+    Rooms {
+        max_rooms: usize,
+        min_room_size: usize,
+        max_room_size: usize,
+        window_chance: f64,
+        traps: usize,
+    },
+    /// Natural caves made with cellular automaton.
+    Caves {
+        fill_percent: u8,
+        smoothing_steps: usize,
+        traps: usize,
+    },
+}
+
+impl Layout {
+    pub fn rooms() -> Self {
+        Layout::Rooms {
+            max_rooms: 8,
+            min_room_size: 3,
+            max_room_size: 8,
+            window_chance: 0.3,
+            traps: 4,
+        }
+    }
+
+    pub fn caves() -> Self {
+        Layout::Caves {
+            fill_percent: 45,
+            smoothing_steps: 4,
+            traps: 6,
+        }
+    }
+}
+
+pub struct GeneratorParams {
+    width: usize,
+    height: usize,
+    seed: u64,
+    layout: Layout,
+}
+
+impl GeneratorParams {
+    pub fn new(width: usize, height: usize, seed: u64, layout: Layout) -> Self {
+        Self {
+            width: width.max(MIN_WIDTH),
+            height: height.max(MIN_HEIGHT),
+            seed,
+            layout,
+        }
+    }
+}
+
+struct Room {
+    x: usize,
+    y: usize,
+    width: usize,
+    height: usize,
+}
+
+impl Room {
+    fn center(&self) -> Vec2 {
+        Vec2::new(
+            (self.x + self.width / 2) as i32,
+            (self.y + self.height / 2) as i32,
+        )
+    }
+
+    /// Checks if rooms overlap or touch each other, so there is at least one wall between them.
+    fn intersects(&self, other: &Room) -> bool {
+        self.x <= other.x + other.width
+            && other.x <= self.x + self.width
+            && self.y <= other.y + other.height
+            && other.y <= self.y + self.height
+    }
+
+    /// Returns positions of the wall which surrounds room, without corners.
+    fn boundary(&self) -> Vec<Vec2> {
+        let left = self.x as i32 - 1;
+        let right = (self.x + self.width) as i32;
+        let top = self.y as i32 - 1;
+        let bottom = (self.y + self.height) as i32;
+
+        let horizontal =
+            (self.x as i32..right).flat_map(|x| vec![Vec2::new(x, top), Vec2::new(x, bottom)]);
+        let vertical =
+            (self.y as i32..bottom).flat_map(|y| vec![Vec2::new(left, y), Vec2::new(right, y)]);
+        horizontal.chain(vertical).collect()
+    }
+}
+
+/// Grid of tiles which is being built. It is converted to `Map` when generation is done.
+struct Grid {
+    width: usize,
+    height: usize,
+    tiles: Vec<MapTile>,
+}
+
+impl Grid {
+    fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            tiles: (0..width * height).map(|_| MapTile::Wall).collect(),
+        }
+    }
+
+    fn get(&self, pos: Vec2) -> Option<&MapTile> {
+        if pos.x < 0 || pos.y < 0 || pos.x as usize >= self.width || pos.y as usize >= self.height {
+            None
+        } else {
+            Some(&self.tiles[pos.to_index(self.width)])
+        }
+    }
+
+    fn set(&mut self, pos: Vec2, tile: MapTile) {
+        self.tiles[pos.to_index(self.width)] = tile;
+    }
+
+    fn is_edge(&self, pos: Vec2) -> bool {
+        pos.x == 0
+            || pos.y == 0
+            || pos.x as usize == self.width - 1
+            || pos.y as usize == self.height - 1
+    }
+
+    fn into_map(self, starting_position: Vec2, seed: u64) -> Map {
+        let details = format!("Generated map, seed {}.\n", seed);
+        Map::from_tiles(
+            self.width,
+            self.height,
+            self.tiles,
+            Some(starting_position),
+            details,
+        )
+    }
+}
+
+/// Builds new map. The same parameters always give the same map.
+pub fn generate(params: &GeneratorParams) -> Map {
+    let mut rng = StdRng::seed_from_u64(params.seed);
+    let mut grid = Grid::new(params.width, params.height);
+
+    let (start, floor, traps) = match params.layout {
+        Layout::Rooms {
+            max_rooms,
+            min_room_size,
+            max_room_size,
+            window_chance,
+            traps,
+        } => {
+            let rooms = place_rooms(&mut grid, &mut rng, max_rooms, min_room_size, max_room_size);
+            for pair in rooms.windows(2) {
+                dig_corridor(&mut grid, &mut rng, pair[0].center(), pair[1].center());
+            }
+            place_doors_and_windows(&mut grid, &mut rng, &rooms, window_chance);
+            (rooms[0].center(), MapTile::Empty, traps)
+        }
+        Layout::Caves {
+            fill_percent,
+            smoothing_steps,
+            traps,
+        } => {
+            grow_caves(&mut grid, &mut rng, fill_percent, smoothing_steps);
+            (keep_largest_cave(&mut grid), MapTile::Grass, traps)
+        }
+    };
+
+    place_traps(&mut grid, &mut rng, &floor, start, traps);
+
+    grid.into_map(start, params.seed)
+}
+
+fn place_rooms(
+    grid: &mut Grid,
+    rng: &mut StdRng,
+    max_rooms: usize,
+    min_size: usize,
+    max_size: usize,
+) -> Vec<Room> {
+    // Room with its walls has to fit into map.
+    let max_width = max_size.min(grid.width - 2).max(1);
+    let max_height = max_size.min(grid.height - 2).max(1);
+    let min_width = min_size.clamp(1, max_width);
+    let min_height = min_size.clamp(1, max_height);
+
+    let mut rooms: Vec<Room> = Vec::new();
+    for _ in 0..ROOM_PLACEMENT_ATTEMPTS {
+        if rooms.len() >= max_rooms.max(1) {
+            break;
+        }
+
+        let width = rng.gen_range(min_width..=max_width);
+        let height = rng.gen_range(min_height..=max_height);
+        let room = Room {
+            x: rng.gen_range(1..=grid.width - width - 1),
+            y: rng.gen_range(1..=grid.height - height - 1),
+            width,
+            height,
+        };
+
+        if rooms.iter().all(|other| !room.intersects(other)) {
+            rooms.push(room);
+        }
+    }
+
+    if rooms.is_empty() {
+        // Map is too crowded for random placement, so use the smallest room in the corner.
+        rooms.push(Room {
+            x: 1,
+            y: 1,
+            width: min_width,
+            height: min_height,
+        });
+    }
+
+    for room in rooms.iter() {
+        for y in room.y..room.y + room.height {
+            for x in room.x..room.x + room.width {
+                grid.set(Vec2::new(x as i32, y as i32), MapTile::Empty);
+            }
+        }
+    }
+
+    rooms
+}
+
+/// Connects two points with L shaped road. Road is dug only through walls.
+fn dig_corridor(grid: &mut Grid, rng: &mut StdRng, from: Vec2, to: Vec2) {
+    let corner = if rng.gen() {
+        Vec2::new(to.x, from.y)
+    } else {
+        Vec2::new(from.x, to.y)
+    };
+
+    for (start, end) in [(from, corner), (corner, to)].iter() {
+        let step = Vec2::new((end.x - start.x).signum(), (end.y - start.y).signum());
+        let mut pos = *start;
+        loop {
+            if grid.get(pos) == Some(&MapTile::Wall) && !grid.is_edge(pos) {
+                grid.set(pos, MapTile::Road);
+            }
+
+            if pos == *end {
+                break;
+            }
+            pos += step;
+        }
+    }
+}
+
+/// Puts doors where roads enter rooms and windows in room walls which face roads.
+fn place_doors_and_windows(grid: &mut Grid, rng: &mut StdRng, rooms: &[Room], window_chance: f64) {
+    let is_wall = |grid: &Grid, pos: Vec2| grid.get(pos) == Some(&MapTile::Wall);
+    let faces_road = |grid: &Grid, pos: Vec2| {
+        [(Vec2::UP, Vec2::DOWN), (Vec2::LEFT, Vec2::RIGHT)]
+            .iter()
+            .any(|(first, second)| {
+                let sides = (grid.get(pos + *first), grid.get(pos + *second));
+                sides == (Some(&MapTile::Road), Some(&MapTile::Empty))
+                    || sides == (Some(&MapTile::Empty), Some(&MapTile::Road))
+            })
+    };
+
+    for room in rooms.iter() {
+        for pos in room.boundary() {
+            let horizontal_wall =
+                is_wall(grid, pos + Vec2::LEFT) && is_wall(grid, pos + Vec2::RIGHT);
+            let vertical_wall = is_wall(grid, pos + Vec2::UP) && is_wall(grid, pos + Vec2::DOWN);
+            if !(horizontal_wall || vertical_wall) {
+                continue;
+            }
+
+            match grid.get(pos) {
+                Some(MapTile::Road) => grid.set(pos, MapTile::Door { locked: false }),
+                Some(MapTile::Wall) if faces_road(grid, pos) && rng.gen_bool(window_chance) => {
+                    grid.set(pos, MapTile::Window { locked: true })
+                }
+                _ => (),
+            }
+        }
+    }
+}
+
+/// Hides traps of random kinds on floor tiles. Starting position is never trapped.
+fn place_traps(grid: &mut Grid, rng: &mut StdRng, floor: &MapTile, start: Vec2, count: usize) {
+    const KINDS: [TrapKind; 4] = [
+        TrapKind::Pit,
+        TrapKind::Spike,
+        TrapKind::Alarm,
+        TrapKind::Teleport,
+    ];
+
+    let mut candidates: Vec<Vec2> = (0..grid.tiles.len())
+        .map(|idx| Vec2::from_index(idx, grid.width))
+        .filter(|pos| *pos != start && grid.get(*pos) == Some(floor))
+        .collect();
+
+    for _ in 0..count {
+        if candidates.is_empty() {
+            break;
+        }
+
+        let pos = candidates.swap_remove(rng.gen_range(0..candidates.len()));
+        let kind = KINDS[rng.gen_range(0..KINDS.len())];
+        grid.set(pos, MapTile::Trap { kind, hidden: true });
+    }
+}
+
+fn grow_caves(grid: &mut Grid, rng: &mut StdRng, fill_percent: u8, smoothing_steps: usize) {
+    for idx in 0..grid.tiles.len() {
+        let pos = Vec2::from_index(idx, grid.width);
+        if !grid.is_edge(pos) && rng.gen_range(0..100) >= fill_percent {
+            grid.set(pos, MapTile::Grass);
+        }
+    }
+
+    for _ in 0..smoothing_steps {
+        let walls_around = |grid: &Grid, pos: Vec2| {
+            let mut count = 0;
+            for y in -1..=1 {
+                for x in -1..=1 {
+                    if (x != 0 || y != 0)
+                        && grid.get(pos + Vec2::new(x, y)) != Some(&MapTile::Grass)
+                    {
+                        count += 1;
+                    }
+                }
+            }
+            count
+        };
+
+        let next: Vec<MapTile> = (0..grid.tiles.len())
+            .map(|idx| {
+                let pos = Vec2::from_index(idx, grid.width);
+                if grid.is_edge(pos) || walls_around(grid, pos) > 4 {
+                    MapTile::Wall
+                } else {
+                    MapTile::Grass
+                }
+            })
+            .collect();
+        grid.tiles = next;
+    }
+}
+
+/// Fills every cave except the biggest one with walls, so whole map is reachable.
+/// Returns starting position, which is the cave tile closest to the map center.
+fn keep_largest_cave(grid: &mut Grid) -> Vec2 {
+    let mut cave_id = vec![None; grid.tiles.len()];
+    let mut caves: Vec<Vec<Vec2>> = Vec::new();
+
+    for idx in 0..grid.tiles.len() {
+        if grid.tiles[idx] != MapTile::Grass || cave_id[idx].is_some() {
+            continue;
+        }
+
+        let origin = Vec2::from_index(idx, grid.width);
+        let cave = pathfinding::flood_fill(grid.width, grid.height, origin, |pos| {
+            grid.get(pos) == Some(&MapTile::Grass)
+        })
+        .reachable();
+
+        for pos in cave.iter() {
+            cave_id[pos.to_index(grid.width)] = Some(caves.len());
+        }
+        caves.push(cave);
+    }
+
+    let largest = (0..caves.len()).max_by_key(|id| caves[*id].len());
+    let center = Vec2::new(grid.width as i32 / 2, grid.height as i32 / 2);
+
+    let largest = match largest {
+        Some(id) => id,
+        None => {
+            // Nothing survived smoothing, so make sure there is at least one free tile.
+            grid.set(center, MapTile::Grass);
+            return center;
+        }
+    };
+
+    for (id, cave) in caves.iter().enumerate() {
+        if id != largest {
+            for pos in cave.iter() {
+                grid.set(*pos, MapTile::Wall);
+            }
+        }
+    }
+
+    *caves[largest]
+        .iter()
+        .min_by_key(|pos| (pos.x - center.x).abs() + (pos.y - center.y).abs())
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::map::validation;
+
+    #[test]
+    fn same_seed_gives_same_map() {
+        let first = generate(&GeneratorParams::new(40, 20, 7, Layout::rooms()));
+        let second = generate(&GeneratorParams::new(40, 20, 7, Layout::rooms()));
+
+        assert_eq!(first.to_map_data(), second.to_map_data());
+    }
+
+    #[test]
+    fn traps_are_hidden_on_floor() {
+        let map = generate(&GeneratorParams::new(40, 20, 3, Layout::rooms()));
+        let traps = (0..map.tiles.len())
+            .filter(|idx| matches!(map.tiles[*idx], MapTile::Trap { hidden: true, .. }))
+            .count();
+
+        assert_eq!(traps, 4);
+        assert!(!matches!(
+            map.tile_at(map.player_position().unwrap()),
+            Some(MapTile::Trap { .. })
+        ));
+    }
+
+    #[test]
+    fn generated_maps_are_valid() {
+        for seed in 0..20 {
+            for layout in [Layout::rooms(), Layout::caves()] {
+                let map = generate(&GeneratorParams::new(60, 30, seed, layout));
+                assert_eq!(validation::validate(&map.to_map_data()), Ok(()));
+            }
+        }
+    }
+}
@@ -0,0 +1,312 @@
+use super::{Map, MapTile, Mobility};
+use crate::vector::Vec2;
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, VecDeque},
+};
+
+const DIRECTIONS: [Vec2; 4] = [Vec2::UP, Vec2::DOWN, Vec2::LEFT, Vec2::RIGHT];
+
+/// Number of steps needed to get from origin to every reachable position on the grid.
+pub struct DistanceMap {
+    width: usize,
+    height: usize,
+    distances: Vec<Option<u32>>,
+}
+
+impl DistanceMap {
+    pub fn distance(&self, pos: Vec2) -> Option<u32> {
+        self.index(pos).and_then(|idx| self.distances[idx])
+    }
+
+    pub fn is_reachable(&self, pos: Vec2) -> bool {
+        self.distance(pos).is_some()
+    }
+
+    /// Returns all reachable positions, including origin.
+    pub fn reachable(&self) -> Vec<Vec2> {
+        self.distances
+            .iter()
+            .enumerate()
+            .filter(|(_, distance)| distance.is_some())
+            .map(|(idx, _)| Vec2::from_index(idx, self.width))
+            .collect()
+    }
+
+    /// Returns steps leading from origin to `target`. Origin itself is not included.
+    pub fn path_to(&self, target: Vec2) -> Option<Vec<Vec2>> {
+        let mut distance = self.distance(target)?;
+        let mut path = vec![target];
+        let mut current = target;
+
+        // Walk downhill back to origin.
+        while distance > 0 {
+            current = DIRECTIONS
+                .iter()
+                .map(|direction| current + *direction)
+                .find(|pos| self.distance(*pos) == Some(distance - 1))?;
+            distance -= 1;
+            path.push(current);
+        }
+
+        path.pop();
+        path.reverse();
+        Some(path)
+    }
+
+    fn index(&self, pos: Vec2) -> Option<usize> {
+        if pos.x < 0 || pos.y < 0 || pos.x as usize >= self.width || pos.y as usize >= self.height {
+            None
+        } else {
+            Some(pos.to_index(self.width))
+        }
+    }
+}
+
+/// Breadth first search from `origin` over grid of given size. Only positions for which
+/// `passable` returns true are visited. Origin is always treated as reachable.
+pub fn flood_fill(
+    width: usize,
+    height: usize,
+    origin: Vec2,
+    passable: impl Fn(Vec2) -> bool,
+) -> DistanceMap {
+    let mut result = DistanceMap {
+        width,
+        height,
+        distances: vec![None; width * height],
+    };
+
+    let origin_idx = match result.index(origin) {
+        Some(idx) => idx,
+        None => return result,
+    };
+
+    let mut queue = VecDeque::new();
+    result.distances[origin_idx] = Some(0);
+    queue.push_back((origin, 0));
+
+    while let Some((pos, distance)) = queue.pop_front() {
+        for direction in DIRECTIONS.iter() {
+            let next = pos + *direction;
+            let next_idx = match result.index(next) {
+                Some(idx) => idx,
+                None => continue,
+            };
+
+            if result.distances[next_idx].is_none() && passable(next) {
+                result.distances[next_idx] = Some(distance + 1);
+                queue.push_back((next, distance + 1));
+            }
+        }
+    }
+
+    result
+}
+
+/// Checks if automatic movement of player with given mobility may go through tile. Traps
+/// player knows about are avoided.
+pub fn is_walkable(map: &Map, pos: Vec2, mobility: Mobility) -> bool {
+    map.can_move_with(pos, mobility)
+        && !matches!(map.tile_at(pos), Some(MapTile::Trap { hidden: false, .. }))
+}
+
+/// Distance to every tile player can walk to from `origin`.
+pub fn distance_map(map: &Map, origin: Vec2, mobility: Mobility) -> DistanceMap {
+    flood_fill(map.width(), map.height(), origin, |pos| {
+        is_walkable(map, pos, mobility)
+    })
+}
+
+/// All tiles player can walk to from `origin`.
+pub fn reachable(map: &Map, origin: Vec2, mobility: Mobility) -> Vec<Vec2> {
+    distance_map(map, origin, mobility).reachable()
+}
+
+/// Cheapest path between two tiles found with A*, which takes tiles movement cost into
+/// account. Returned steps don't include `from`.
+pub fn find_path(map: &Map, from: Vec2, to: Vec2, mobility: Mobility) -> Option<Vec<Vec2>> {
+    if from == to {
+        return Some(vec![]);
+    }
+
+    if !is_walkable(map, to, mobility) {
+        return None;
+    }
+
+    let heuristic = |pos: Vec2| ((pos.x - to.x).abs() + (pos.y - to.y).abs()) as u32;
+
+    let mut open = BinaryHeap::new();
+    let mut came_from: HashMap<Vec2, Vec2> = HashMap::new();
+    let mut cost: HashMap<Vec2, u32> = HashMap::new();
+
+    cost.insert(from, 0);
+    open.push(Reverse((heuristic(from), 0, from.x, from.y)));
+
+    while let Some(Reverse((_, current_cost, x, y))) = open.pop() {
+        let current = Vec2::new(x, y);
+        if current == to {
+            let mut path = vec![current];
+            let mut step = current;
+            while let Some(previous) = came_from.get(&step) {
+                if *previous == from {
+                    break;
+                }
+                step = *previous;
+                path.push(step);
+            }
+
+            path.reverse();
+            return Some(path);
+        }
+
+        if current_cost > cost[&current] {
+            // Stale entry, this tile was already reached with lower cost.
+            continue;
+        }
+
+        for direction in DIRECTIONS.iter() {
+            let next = current + *direction;
+            if !is_walkable(map, next, mobility) {
+                continue;
+            }
+
+            let next_cost = current_cost + map.movement_cost(next);
+            if cost.get(&next).is_none_or(|known| next_cost < *known) {
+                cost.insert(next, next_cost);
+                came_from.insert(next, current);
+                open.push(Reverse((
+                    next_cost + heuristic(next),
+                    next_cost,
+                    next.x,
+                    next.y,
+                )));
+            }
+        }
+    }
+
+    None
+}
+
+/// Finds closest reachable tile for which `is_target` returns true and path leading to it.
+pub fn nearest(
+    map: &Map,
+    origin: Vec2,
+    mobility: Mobility,
+    is_target: impl Fn(Vec2, &MapTile) -> bool,
+) -> Option<(Vec2, Vec<Vec2>)> {
+    let distances = distance_map(map, origin, mobility);
+
+    let target = distances
+        .reachable()
+        .into_iter()
+        .filter(|pos| *pos != origin)
+        .filter(|pos| {
+            map.get_tile(pos.x as usize, pos.y as usize)
+                .is_some_and(|tile| is_target(*pos, tile))
+        })
+        .min_by_key(|pos| (distances.distance(*pos), pos.y, pos.x))?;
+
+    let path = distances.path_to(target)?;
+    Some((target, path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_map() -> Map {
+        let data = "#######\n#@  D #\n### ###\n#.    #\n#######\n";
+        Map::parse_data(data.to_string(), String::new())
+    }
+
+    #[test]
+    fn distances_and_reachability() {
+        let map = test_map();
+        let start = map.player_position().unwrap();
+        let distances = distance_map(&map, start, Mobility::default());
+
+        assert_eq!(distances.distance(Vec2::new(3, 3)), Some(4));
+        assert_eq!(distances.distance(Vec2::new(1, 3)), Some(6));
+        // Locked door blocks the way.
+        assert!(!distances.is_reachable(Vec2::new(5, 1)));
+        assert_eq!(reachable(&map, start, Mobility::default()).len(), 9);
+    }
+
+    #[test]
+    fn shortest_path() {
+        let map = test_map();
+        let start = map.player_position().unwrap();
+        let path = find_path(&map, start, Vec2::new(3, 3), Mobility::default()).unwrap();
+
+        assert_eq!(
+            path,
+            vec![
+                Vec2::new(2, 1),
+                Vec2::new(3, 1),
+                Vec2::new(3, 2),
+                Vec2::new(3, 3)
+            ]
+        );
+        assert_eq!(
+            distance_map(&map, start, Mobility::default()).path_to(Vec2::new(3, 3)),
+            Some(path)
+        );
+        assert!(find_path(&map, start, Vec2::new(5, 1), Mobility::default()).is_none());
+    }
+
+    #[test]
+    fn detected_traps_are_avoided() {
+        let data = "#####\n#@^ #\n#   #\n#####\n";
+        let mut map = Map::parse_data(data.to_string(), String::new());
+        let start = map.player_position().unwrap();
+        let target = Vec2::new(3, 1);
+
+        // Hidden trap is not known, so the shortest way leads through it.
+        assert_eq!(
+            find_path(&map, start, target, Mobility::default())
+                .unwrap()
+                .len(),
+            2
+        );
+
+        map.reveal_trap(Vec2::new(2, 1));
+        let path = find_path(&map, start, target, Mobility::default()).unwrap();
+        assert_eq!(path.len(), 4);
+        assert!(!path.contains(&Vec2::new(2, 1)));
+    }
+
+    #[test]
+    fn nearest_target() {
+        let map = test_map();
+        let start = map.player_position().unwrap();
+        let (target, path) = nearest(&map, start, Mobility::default(), |_, tile| {
+            *tile == MapTile::Grass
+        })
+        .unwrap();
+
+        assert_eq!(target, Vec2::new(1, 3));
+        assert_eq!(path.len(), 6);
+    }
+
+    #[test]
+    fn deep_water_needs_swimming() {
+        let data = "#####\n#@~ #\n#####\n";
+        let map = Map::parse_data(data.to_string(), String::new());
+        let start = map.player_position().unwrap();
+        let target = Vec2::new(3, 1);
+
+        assert!(find_path(&map, start, target, Mobility::default()).is_none());
+        assert!(!distance_map(&map, start, Mobility::default()).is_reachable(target));
+
+        let swimmer = Mobility {
+            swim: true,
+            boat: false,
+        };
+        let path = find_path(&map, start, target, swimmer).unwrap();
+        assert_eq!(path, vec![Vec2::new(2, 1), target]);
+        assert_eq!(distance_map(&map, start, swimmer).distance(target), Some(2));
+        let (found, _) = nearest(&map, start, swimmer, |pos, _| pos == target).unwrap();
+        assert_eq!(found, target);
+    }
+}
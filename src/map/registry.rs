@@ -0,0 +1,324 @@
+use super::{Passability, TileProperties};
+use crate::theme::{self, Connection, TileStyle};
+use std::{
+    collections::HashMap,
+    fs,
+    io::{self, ErrorKind},
+    sync::OnceLock,
+};
+
+pub const TILES_FILE: &str = "data/tiles.def";
+
+static REGISTRY: OnceLock<TileRegistry> = OnceLock::new();
+
+/// Keys of tiles, which have their own behaviour in game. Registry has to define all of them.
+pub mod key {
+    pub const EMPTY: &str = "empty";
+    pub const GRASS: &str = "grass";
+    pub const ROAD: &str = "road";
+    pub const WALL: &str = "wall";
+    pub const DOOR_LOCKED: &str = "door_locked";
+    pub const DOOR_UNLOCKED: &str = "door_unlocked";
+    pub const WINDOW_LOCKED: &str = "window_locked";
+    pub const WINDOW_UNLOCKED: &str = "window_unlocked";
+    pub const WATER_DEEP: &str = "water_deep";
+    pub const WATER_SHALLOW: &str = "water_shallow";
+    pub const TREE: &str = "tree";
+    pub const BUSH: &str = "bush";
+    pub const STAIRS_UP: &str = "stairs_up";
+    pub const STAIRS_DOWN: &str = "stairs_down";
+    pub const CHEST: &str = "chest";
+    pub const TRAP_PIT: &str = "trap_pit";
+    pub const TRAP_SPIKE: &str = "trap_spike";
+    pub const TRAP_ALARM: &str = "trap_alarm";
+    pub const TRAP_TELEPORT: &str = "trap_teleport";
+    pub const LAVA: &str = "lava";
+    pub const SIGNPOST: &str = "signpost";
+    pub const PLAYER: &str = "player";
+    /// Items lying on the floor. Drawn over map, so it is not a real tile.
+    pub const PICKUP: &str = "pickup";
+
+    pub const ALL: [&str; 23] = [
+        EMPTY,
+        GRASS,
+        ROAD,
+        WALL,
+        DOOR_LOCKED,
+        DOOR_UNLOCKED,
+        WINDOW_LOCKED,
+        WINDOW_UNLOCKED,
+        WATER_DEEP,
+        WATER_SHALLOW,
+        TREE,
+        BUSH,
+        STAIRS_UP,
+        STAIRS_DOWN,
+        CHEST,
+        TRAP_PIT,
+        TRAP_SPIKE,
+        TRAP_ALARM,
+        TRAP_TELEPORT,
+        LAVA,
+        SIGNPOST,
+        PLAYER,
+        PICKUP,
+    ];
+}
+
+/// What happens when player interacts with tile.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Hook {
+    /// Bumping into tile shows text written on it.
+    ReadSign,
+    /// Bumping into tile opens container placed on it.
+    OpenContainer,
+    /// Bumping into tile starts trade with merchant standing there.
+    Trade,
+    /// Bumping into tile starts dialogue with NPC standing there.
+    Talk,
+}
+
+/// Everything game needs to know about one kind of tile.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TileDef {
+    pub key: String,
+    /// Character used for this tile in map files. Definitions without it are used
+    /// only for drawing things placed over map.
+    pub map_char: Option<char>,
+    pub style: TileStyle,
+    pub connection: Option<Connection>,
+    pub properties: TileProperties,
+    pub hook: Option<Hook>,
+}
+
+/// All tile definitions, loaded from tiles file.
+pub struct TileRegistry {
+    tiles: HashMap<String, TileDef>,
+    by_char: HashMap<char, String>,
+}
+
+/// Returns registry used by the game. It is loaded from default file when it wasn't set
+/// with `init` before.
+pub fn get() -> &'static TileRegistry {
+    REGISTRY.get_or_init(|| {
+        TileRegistry::load_from_file(TILES_FILE)
+            .unwrap_or_else(|error| panic!("{}: {}", TILES_FILE, error))
+    })
+}
+
+/// Sets registry used by the game. It can be done only once, before first `get`.
+pub fn init(registry: TileRegistry) -> bool {
+    REGISTRY.set(registry).is_ok()
+}
+
+impl TileRegistry {
+    pub fn load_from_file(file_name: &str) -> io::Result<Self> {
+        let raw_data = fs::read_to_string(file_name)?;
+        Self::parse(&raw_data).map_err(|error| io::Error::new(ErrorKind::InvalidData, error))
+    }
+
+    pub fn contains(&self, key: &str) -> bool {
+        self.tiles.contains_key(key)
+    }
+
+    pub fn tile(&self, key: &str) -> &TileDef {
+        &self.tiles[key]
+    }
+
+    /// Key of the tile written in map files with given character.
+    pub fn key_for_char(&self, ch: char) -> Option<&str> {
+        self.by_char.get(&ch).map(|key| key.as_str())
+    }
+
+    fn parse(raw_data: &str) -> Result<Self, String> {
+        let mut tiles: HashMap<String, TileDef> = HashMap::new();
+        let mut by_char = HashMap::new();
+        let mut current: Option<(usize, PartialDef)> = None;
+
+        let mut finish = |current: Option<(usize, PartialDef)>| -> Result<(), String> {
+            if let Some((line_number, partial)) = current {
+                let def = partial
+                    .build()
+                    .map_err(|error| format!("line {}: {}", line_number, error))?;
+                if let Some(map_char) = def.map_char {
+                    if let Some(other) = by_char.insert(map_char, def.key.clone()) {
+                        return Err(format!(
+                            "line {}: char '{}' is already used by '{}'",
+                            line_number, map_char, other
+                        ));
+                    }
+                }
+                tiles.insert(def.key.clone(), def);
+            }
+            Ok(())
+        };
+
+        for (idx, line) in raw_data.lines().enumerate() {
+            let line_number = idx + 1;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with(';') {
+                continue;
+            }
+
+            if line.starts_with('[') && line.ends_with(']') {
+                finish(current.take())?;
+                let key = line[1..line.len() - 1].trim().to_string();
+                current = Some((line_number, PartialDef::new(key)));
+                continue;
+            }
+
+            match current.as_mut() {
+                Some((_, partial)) => partial
+                    .set(line)
+                    .map_err(|error| format!("line {}: {}", line_number, error))?,
+                None => return Err(format!("line {}: text outside of section", line_number)),
+            }
+        }
+        finish(current.take())?;
+
+        if let Some(missing) = key::ALL.iter().find(|key| !tiles.contains_key(**key)) {
+            return Err(format!("tile '{}' is missing", missing));
+        }
+
+        for def in tiles.values() {
+            let mut connects_to = def.connection.iter().flat_map(|c| c.connects_to.iter());
+            if let Some(unknown) = connects_to.find(|key| !tiles.contains_key(*key)) {
+                return Err(format!(
+                    "tile '{}' connects to unknown tile '{}'",
+                    def.key, unknown
+                ));
+            }
+        }
+
+        Ok(Self { tiles, by_char })
+    }
+}
+
+/// Tile definition, which is still being read from file.
+struct PartialDef {
+    key: String,
+    map_char: Option<char>,
+    style: Option<TileStyle>,
+    connection: Option<Connection>,
+    properties: TileProperties,
+    hook: Option<Hook>,
+}
+
+impl PartialDef {
+    fn new(key: String) -> Self {
+        Self {
+            key,
+            map_char: None,
+            style: None,
+            connection: None,
+            properties: TileProperties {
+                passability: Passability::Open,
+                blocks_sight: false,
+                movement_cost: 1,
+                damage: 0,
+            },
+            hook: None,
+        }
+    }
+
+    /// Sets property from line in format: `name = value`.
+    fn set(&mut self, line: &str) -> Result<(), String> {
+        let mut parts = line.splitn(2, '=');
+        let name = parts.next().unwrap_or("").trim();
+        let value = parts.next().ok_or("missing '='")?.trim();
+
+        match name {
+            "char" => self.map_char = Some(theme::parse_quoted_glyph(value)?.0),
+            "style" => self.style = Some(theme::parse_style_value(value)?),
+            "connected" => self.connection = Some(theme::parse_connection_value(value)?),
+            "passability" => {
+                self.properties.passability = match value {
+                    "open" => Passability::Open,
+                    "water" => Passability::Water,
+                    "blocked" => Passability::Blocked,
+                    _ => return Err(format!("unknown passability '{}'", value)),
+                }
+            }
+            "blocks_sight" => self.properties.blocks_sight = parse_bool(value)?,
+            "cost" => self.properties.movement_cost = parse_number(value)?,
+            "damage" => self.properties.damage = parse_number(value)?,
+            "hook" => {
+                self.hook = match value {
+                    "none" => None,
+                    "read_sign" => Some(Hook::ReadSign),
+                    "open_container" => Some(Hook::OpenContainer),
+                    "trade" => Some(Hook::Trade),
+                    "talk" => Some(Hook::Talk),
+                    _ => return Err(format!("unknown hook '{}'", value)),
+                }
+            }
+            _ => return Err(format!("unknown property '{}'", name)),
+        }
+
+        Ok(())
+    }
+
+    fn build(self) -> Result<TileDef, String> {
+        Ok(TileDef {
+            map_char: self.map_char,
+            style: self
+                .style
+                .ok_or(format!("tile '{}' has no style", self.key))?,
+            key: self.key,
+            connection: self.connection,
+            properties: self.properties,
+            hook: self.hook,
+        })
+    }
+}
+
+fn parse_bool(value: &str) -> Result<bool, String> {
+    match value {
+        "yes" => Ok(true),
+        "no" => Ok(false),
+        _ => Err(format!("expected 'yes' or 'no', found '{}'", value)),
+    }
+}
+
+fn parse_number<T: std::str::FromStr>(value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("'{}' is not a valid number", value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bundled_tiles_load() {
+        let registry = TileRegistry::load_from_file(TILES_FILE).unwrap();
+
+        assert_eq!(registry.key_for_char('#'), Some(key::WALL));
+        assert_eq!(registry.tile(key::SIGNPOST).hook, Some(Hook::ReadSign));
+        assert_eq!(registry.tile(key::LAVA).properties.damage, 5);
+        assert!(registry.tile(key::ROAD).connection.is_some());
+    }
+
+    #[test]
+    fn parse_errors() {
+        let duplicate_char = "[a]\nchar = 'x'\nstyle = 'x' Reset Reset\n\
+                              [b]\nchar = 'x'\nstyle = 'y' Reset Reset\n";
+        assert_eq!(
+            TileRegistry::parse(duplicate_char).err(),
+            Some("line 4: char 'x' is already used by 'a'".to_string())
+        );
+
+        let bad_cost = "[a]\ncost = many\n";
+        assert_eq!(
+            TileRegistry::parse(bad_cost).err(),
+            Some("line 2: 'many' is not a valid number".to_string())
+        );
+
+        let no_style = "[a]\nchar = 'x'\n";
+        assert_eq!(
+            TileRegistry::parse(no_style).err(),
+            Some("line 1: tile 'a' has no style".to_string())
+        );
+    }
+}
@@ -0,0 +1,82 @@
+use super::registry::key;
+use core::fmt;
+
+/// Tiles around player, which are searched for hidden traps after every step.
+pub const DETECTION_RADIUS: i32 = 2;
+const BASE_DETECTION_CHANCE: f64 = 0.1;
+/// Detection chance added by every point of the better of intelligence and dexterity.
+const DETECTION_CHANCE_PER_POINT: f64 = 0.04;
+const MAX_DETECTION_CHANCE: f64 = 0.9;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TrapKind {
+    /// Player falls down and gets hurt.
+    Pit,
+    /// Spikes come out of the floor and hurt player.
+    Spike,
+    /// Noise brings enemy to the player.
+    Alarm,
+    /// Player is moved to random reachable place.
+    Teleport,
+}
+
+impl TrapKind {
+    pub fn from_key(tile_key: &str) -> Option<Self> {
+        match tile_key {
+            key::TRAP_PIT => Some(TrapKind::Pit),
+            key::TRAP_SPIKE => Some(TrapKind::Spike),
+            key::TRAP_ALARM => Some(TrapKind::Alarm),
+            key::TRAP_TELEPORT => Some(TrapKind::Teleport),
+            _ => None,
+        }
+    }
+
+    pub fn key(&self) -> &'static str {
+        match self {
+            TrapKind::Pit => key::TRAP_PIT,
+            TrapKind::Spike => key::TRAP_SPIKE,
+            TrapKind::Alarm => key::TRAP_ALARM,
+            TrapKind::Teleport => key::TRAP_TELEPORT,
+        }
+    }
+
+    /// Text shown when trap is triggered.
+    pub fn description(&self) -> &'static str {
+        match self {
+            TrapKind::Pit => "The floor gives way and you fall into a pit.",
+            TrapKind::Spike => "Spikes shoot out of the floor.",
+            TrapKind::Alarm => "Loud bell rings. Something is coming!",
+            TrapKind::Teleport => "Runes flash and you find yourself somewhere else.",
+        }
+    }
+}
+
+impl fmt::Display for TrapKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            TrapKind::Pit => "pit",
+            TrapKind::Spike => "spike trap",
+            TrapKind::Alarm => "alarm trap",
+            TrapKind::Teleport => "teleport trap",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Chance of noticing one hidden trap nearby. Sharp mind or quick eye are equally good.
+pub fn detection_chance(intelligence: u8, dexterity: u8) -> f64 {
+    let skill = intelligence.max(dexterity) as f64;
+    (BASE_DETECTION_CHANCE + skill * DETECTION_CHANCE_PER_POINT).min(MAX_DETECTION_CHANCE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn better_stat_is_used_for_detection() {
+        assert_eq!(detection_chance(5, 2), detection_chance(2, 5));
+        assert!(detection_chance(7, 0) > detection_chance(3, 0));
+        assert_eq!(detection_chance(u8::MAX, 0), MAX_DETECTION_CHANCE);
+    }
+}
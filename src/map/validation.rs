@@ -0,0 +1,322 @@
+use super::{pathfinding, Map, MapTile};
+use crate::vector::Vec2;
+use core::fmt;
+
+/// Problem found in map file. Positions are always reported for raw file contents.
+#[derive(Debug, PartialEq)]
+pub enum Issue {
+    UnknownGlyph(char),
+    RaggedRow {
+        expected: usize,
+        found: usize,
+    },
+    MissingStart,
+    DuplicateStart {
+        first_line: usize,
+        first_column: usize,
+    },
+    UnreachableArea {
+        tiles: usize,
+    },
+    OpenEdge,
+    /// Entry of map section, like container or NPC, can't be parsed.
+    InvalidEntry(String),
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Diagnostic {
+    pub line: usize,
+    pub column: usize,
+    pub issue: Issue,
+}
+
+impl Diagnostic {
+    fn new(line: usize, column: usize, issue: Issue) -> Self {
+        Self {
+            line,
+            column,
+            issue,
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: ", self.line, self.column)?;
+
+        match &self.issue {
+            Issue::UnknownGlyph(ch) => write!(f, "unknown glyph '{}'", ch),
+            Issue::RaggedRow { expected, found } => {
+                write!(f, "row has {} tiles, but {} was expected", found, expected)
+            }
+            Issue::MissingStart => write!(f, "map has no starting position '@'"),
+            Issue::DuplicateStart {
+                first_line,
+                first_column,
+            } => write!(
+                f,
+                "duplicate starting position, first one is at {}:{}",
+                first_line, first_column
+            ),
+            Issue::UnreachableArea { tiles } => write!(
+                f,
+                "area of {} tile(s) can't be reached from starting position",
+                tiles
+            ),
+            Issue::OpenEdge => write!(f, "map edge is not enclosed by wall"),
+            Issue::InvalidEntry(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+/// Row of map file with its line number and parsed tiles. Unknown glyphs are kept as `None`.
+struct Row<'a> {
+    line: usize,
+    text: &'a str,
+    tiles: Vec<Option<MapTile>>,
+}
+
+/// Checks raw map file data and returns every problem found in it.
+pub fn validate(raw_data: &str) -> Result<(), Vec<Diagnostic>> {
+    let mut diagnostics = Vec::new();
+    let mut start: Option<(usize, usize, Vec2)> = None;
+
+    let rows: Vec<Row> = Map::map_lines(raw_data)
+        .map(|(line, text)| Row {
+            line,
+            text,
+            tiles: text.chars().map(Map::tile_from_char).collect(),
+        })
+        .collect();
+
+    for (y, row) in rows.iter().enumerate() {
+        for (x, (ch, tile)) in row.text.chars().zip(row.tiles.iter()).enumerate() {
+            let column = x + 1;
+            match tile {
+                None => {
+                    diagnostics.push(Diagnostic::new(row.line, column, Issue::UnknownGlyph(ch)))
+                }
+                Some(MapTile::Player) => match start {
+                    Some((first_line, first_column, _)) => diagnostics.push(Diagnostic::new(
+                        row.line,
+                        column,
+                        Issue::DuplicateStart {
+                            first_line,
+                            first_column,
+                        },
+                    )),
+                    None => start = Some((row.line, column, Vec2::new(x as i32, y as i32))),
+                },
+                _ => (),
+            }
+        }
+    }
+
+    let expected = rows.first().map_or(0, |row| row.tiles.len());
+    for row in rows.iter().filter(|row| row.tiles.len() != expected) {
+        let found = row.tiles.len();
+        diagnostics.push(Diagnostic::new(
+            row.line,
+            found.min(expected) + 1,
+            Issue::RaggedRow { expected, found },
+        ));
+    }
+
+    diagnostics.extend(check_edges(&rows));
+
+    match start {
+        Some((_, _, position)) => diagnostics.extend(check_reachability(&rows, position)),
+        None => diagnostics.push(Diagnostic::new(
+            rows.last().map_or(1, |row| row.line),
+            1,
+            Issue::MissingStart,
+        )),
+    }
+
+    diagnostics.extend(
+        Map::section_errors(raw_data)
+            .into_iter()
+            .map(|error| Diagnostic::new(error.line, 1, Issue::InvalidEntry(error.message))),
+    );
+
+    diagnostics.sort_by_key(|diagnostic| (diagnostic.line, diagnostic.column));
+
+    if diagnostics.is_empty() {
+        Ok(())
+    } else {
+        Err(diagnostics)
+    }
+}
+
+fn check_edges(rows: &[Row]) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+
+    for (y, row) in rows.iter().enumerate() {
+        let edge_row = y == 0 || y == rows.len() - 1;
+        for (x, tile) in row.tiles.iter().enumerate() {
+            let edge_column = x == 0 || x == row.tiles.len() - 1;
+            if (edge_row || edge_column) && *tile != Some(MapTile::Wall) {
+                diagnostics.push(Diagnostic::new(row.line, x + 1, Issue::OpenEdge));
+            }
+        }
+    }
+
+    diagnostics
+}
+
+/// Finds passable areas which player can't get to. Doors are treated as passable even
+/// if they are locked, because player may open them later.
+fn check_reachability(rows: &[Row], start: Vec2) -> Vec<Diagnostic> {
+    let walkable = |pos: Vec2| -> bool {
+        if pos.x < 0 || pos.y < 0 {
+            return false;
+        }
+
+        match rows
+            .get(pos.y as usize)
+            .and_then(|row| row.tiles.get(pos.x as usize))
+        {
+            Some(Some(MapTile::Door { .. })) => true,
+            Some(Some(tile)) => tile.is_passable(),
+            // Unknown glyphs are loaded as empty tiles.
+            Some(None) => true,
+            None => false,
+        }
+    };
+
+    let width = rows.iter().map(|row| row.tiles.len()).max().unwrap_or(0);
+    let mut visited = vec![false; width * rows.len()];
+    let flood = |from: Vec2, visited: &mut Vec<bool>| -> usize {
+        let area = pathfinding::flood_fill(width, rows.len(), from, walkable).reachable();
+        for pos in area.iter() {
+            visited[pos.to_index(width)] = true;
+        }
+        area.len()
+    };
+
+    flood(start, &mut visited);
+
+    let mut diagnostics = Vec::new();
+    for (y, row) in rows.iter().enumerate() {
+        for x in 0..row.tiles.len() {
+            let pos = Vec2::new(x as i32, y as i32);
+            if walkable(pos) && !visited[pos.to_index(width)] {
+                let tiles = flood(pos, &mut visited);
+                diagnostics.push(Diagnostic::new(
+                    row.line,
+                    x + 1,
+                    Issue::UnreachableArea { tiles },
+                ));
+            }
+        }
+    }
+
+    diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn valid_map() {
+        let data = "; comment\n#####\n#@ d#\n#####\n\n[details]\nSome text\n";
+        assert_eq!(validate(data), Ok(()));
+    }
+
+    #[test]
+    fn bundled_map_is_valid() {
+        let data = std::fs::read_to_string("data/Maps/Test.map").unwrap();
+        assert_eq!(validate(&data), Ok(()));
+    }
+
+    #[test]
+    fn unknown_glyph_and_ragged_row() {
+        let data = "#####\n#@?#\n#####\n";
+        let diagnostics = validate(data).unwrap_err();
+
+        assert_eq!(
+            diagnostics,
+            vec![
+                Diagnostic::new(2, 3, Issue::UnknownGlyph('?')),
+                Diagnostic::new(
+                    2,
+                    5,
+                    Issue::RaggedRow {
+                        expected: 5,
+                        found: 4
+                    }
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn start_position() {
+        let missing = validate("###\n# #\n###\n").unwrap_err();
+        assert_eq!(missing, vec![Diagnostic::new(3, 1, Issue::MissingStart)]);
+
+        let duplicate = validate("####\n#@@#\n####\n").unwrap_err();
+        assert_eq!(
+            duplicate,
+            vec![Diagnostic::new(
+                2,
+                3,
+                Issue::DuplicateStart {
+                    first_line: 2,
+                    first_column: 2
+                }
+            )]
+        );
+    }
+
+    #[test]
+    fn unreachable_area_and_open_edge() {
+        let data = "######\n#@#  #\n###### \n";
+        let diagnostics = validate(data).unwrap_err();
+
+        assert!(diagnostics.contains(&Diagnostic::new(2, 4, Issue::UnreachableArea { tiles: 2 })));
+        assert!(diagnostics.contains(&Diagnostic::new(3, 7, Issue::OpenEdge)));
+    }
+
+    #[test]
+    fn invalid_section_entries() {
+        let data = "####\n#@ #\n####\n\n[signs]\n1,1 = Hello\n[npcs]\n; comment\n\
+                    2,1 = @nobody Ghost\n[containers]\n1 = @chest\n[triggers]\n\
+                    1,1 = set_flag seen\n2,1 = teleport\n[scripts]\n1,1 = on_enter shrine\n\
+                    2,1 = on_use no_such_script\n[traps]\n1,1 = hidden\n";
+        let diagnostics = validate(data).unwrap_err();
+
+        assert_eq!(
+            diagnostics,
+            vec![
+                Diagnostic::new(
+                    9,
+                    1,
+                    Issue::InvalidEntry("unknown dialogue 'nobody'".to_string())
+                ),
+                Diagnostic::new(
+                    11,
+                    1,
+                    Issue::InvalidEntry("invalid position '1'".to_string())
+                ),
+                Diagnostic::new(
+                    14,
+                    1,
+                    Issue::InvalidEntry("unknown effect 'teleport'".to_string())
+                ),
+                Diagnostic::new(
+                    17,
+                    1,
+                    Issue::InvalidEntry("unknown script 'no_such_script'".to_string())
+                ),
+                Diagnostic::new(
+                    19,
+                    1,
+                    Issue::InvalidEntry("unknown trap state 'hidden'".to_string())
+                ),
+            ]
+        );
+        assert_eq!(diagnostics[0].to_string(), "9:1: unknown dialogue 'nobody'");
+    }
+}
@@ -0,0 +1,112 @@
+use crate::{item::Item, loot};
+use rand::Rng;
+
+/// Merchant standing on map, who buys and sells items. Stock is rolled from loot table and
+/// rolled again after given number of turns.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Merchant {
+    table: String,
+    restock_turns: u32,
+    turns_left: u32,
+    stock: Vec<Item>,
+}
+
+impl Merchant {
+    /// Parses merchant in format: `@table restock_turns`.
+    pub fn parse(text: &str, rng: &mut impl Rng) -> Result<Self, String> {
+        let parts: Vec<&str> = text.split_whitespace().collect();
+        let (table, restock_turns) = match parts.as_slice() {
+            [table, turns] => match (table.strip_prefix('@'), turns.parse()) {
+                (Some(table), Ok(turns)) if turns > 0 => (table, turns),
+                _ => return Err(format!("invalid merchant '{}'", text)),
+            },
+            _ => return Err("expected @table and restock turns".to_string()),
+        };
+
+        if loot::tables().get(table).is_none() {
+            return Err(format!("unknown loot table '{}'", table));
+        }
+
+        let mut merchant = Self {
+            table: table.to_string(),
+            restock_turns,
+            turns_left: restock_turns,
+            stock: Vec::new(),
+        };
+        merchant.restock(rng);
+        Ok(merchant)
+    }
+
+    /// Text form of merchant, which can be read back with `parse`. Stock isn't saved, it is
+    /// rolled again on load.
+    pub fn to_code(&self) -> String {
+        format!("@{} {}", self.table, self.restock_turns)
+    }
+
+    pub fn stock(&self) -> &[Item] {
+        &self.stock
+    }
+
+    /// Counts down one turn and replaces stock with new items when time comes.
+    pub fn pass_turn(&mut self, rng: &mut impl Rng) {
+        self.turns_left -= 1;
+        if self.turns_left == 0 {
+            self.restock(rng);
+        }
+    }
+
+    fn restock(&mut self, rng: &mut impl Rng) {
+        self.stock = loot::tables()
+            .get(&self.table)
+            .map(|table| table.roll(rng).items)
+            .unwrap_or_default();
+        self.turns_left = self.restock_turns;
+    }
+
+    /// Removes item from stock if buyer can pay for it. Returns bought item and its price.
+    pub fn sell_to(&mut self, index: usize, gold: u32) -> Option<(Item, u32)> {
+        let price = self.stock.get(index)?.value();
+        if price > gold {
+            return None;
+        }
+        Some((self.stock.remove(index), price))
+    }
+
+    /// Takes item from player and returns gold paid for it.
+    pub fn buy_from(&mut self, item: Item) -> u32 {
+        let price = item.sell_value();
+        self.stock.push(item);
+        price
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn trade_and_restock() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut merchant = Merchant::parse("@merchant 2", &mut rng).unwrap();
+        assert_eq!(merchant.to_code(), "@merchant 2");
+
+        let lockpick = Item::lockpick();
+        assert_eq!(merchant.buy_from(lockpick.clone()), lockpick.sell_value());
+        let index = merchant.stock().len() - 1;
+        assert_eq!(merchant.sell_to(index, 0), None);
+        assert_eq!(
+            merchant.sell_to(index, 100),
+            Some((lockpick.clone(), lockpick.value()))
+        );
+
+        while merchant.sell_to(0, u32::MAX).is_some() {}
+        merchant.pass_turn(&mut rng);
+        assert!(merchant.stock().is_empty());
+        merchant.pass_turn(&mut rng);
+        assert!(!merchant.stock().is_empty());
+
+        assert!(Merchant::parse("@merchant 0", &mut rng).is_err());
+        assert!(Merchant::parse("@no_such_table 5", &mut rng).is_err());
+    }
+}
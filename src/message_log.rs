@@ -0,0 +1,90 @@
+/// Oldest messages are forgotten when log grows over this size.
+pub const MAX_MESSAGES: usize = 500;
+
+/// Kind of message, shown in its own color.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Category {
+    /// Enemies, battles and traps.
+    Combat,
+    /// Found, taken, bought and sold items and gold.
+    Loot,
+    /// Quests, saving and everything else.
+    System,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Message {
+    pub category: Category,
+    pub text: String,
+}
+
+/// History of everything that happened to player, newest message is the last one.
+#[derive(Debug, Default)]
+pub struct MessageLog {
+    messages: Vec<Message>,
+}
+
+impl MessageLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, category: Category, text: impl Into<String>) {
+        self.messages.push(Message {
+            category,
+            text: text.into(),
+        });
+        if self.messages.len() > MAX_MESSAGES {
+            self.messages.remove(0);
+        }
+    }
+
+    pub fn messages(&self) -> &[Message] {
+        &self.messages
+    }
+
+    /// Index of the newest message older than `before` containing query, case is ignored.
+    pub fn find_older(&self, query: &str, before: usize) -> Option<usize> {
+        let before = before.min(self.messages.len());
+        (0..before).rev().find(|idx| self.matches(*idx, query))
+    }
+
+    /// Index of the oldest message newer than `after` containing query, case is ignored.
+    pub fn find_newer(&self, query: &str, after: usize) -> Option<usize> {
+        (after + 1..self.messages.len()).find(|idx| self.matches(*idx, query))
+    }
+
+    /// True if message with given index contains query. Empty query matches nothing.
+    pub fn matches(&self, index: usize, query: &str) -> bool {
+        !query.is_empty()
+            && self
+                .messages
+                .get(index)
+                .is_some_and(|message| message.text.to_lowercase().contains(&query.to_lowercase()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn search_messages() {
+        let mut log = MessageLog::new();
+        log.add(Category::Combat, "You meet goblin");
+        log.add(Category::Loot, "Found Key");
+        log.add(Category::Combat, "Goblin defeated");
+
+        assert_eq!(log.find_older("goblin", log.messages().len()), Some(2));
+        assert_eq!(log.find_older("goblin", 2), Some(0));
+        assert_eq!(log.find_older("goblin", 0), None);
+        assert_eq!(log.find_newer("GOBLIN", 0), Some(2));
+        assert_eq!(log.find_newer("", 0), None);
+
+        for idx in 0..MAX_MESSAGES {
+            log.add(Category::System, format!("Message {}", idx));
+        }
+        assert_eq!(log.messages().len(), MAX_MESSAGES);
+        assert_eq!(log.find_older("goblin", MAX_MESSAGES), None);
+    }
+}
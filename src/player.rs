@@ -1,4 +1,122 @@
-use crate::{item::Item, vector::Vec2};
+use crate::{dialogue::Stat, item::Item, map::Mobility, vector::Vec2};
+
+const XP_PER_LEVEL: u8 = 10;
+
+/// Equipment slot.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Slot {
+    Head,
+    Chest,
+    Legs,
+    Arms,
+    Feet,
+    LeftHand,
+    RightHand,
+}
+
+impl Slot {
+    pub const ALL: [Slot; 7] = [
+        Slot::Head,
+        Slot::Chest,
+        Slot::Legs,
+        Slot::Arms,
+        Slot::Feet,
+        Slot::LeftHand,
+        Slot::RightHand,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Slot::Head => "Head",
+            Slot::Chest => "Chest",
+            Slot::Legs => "Legs",
+            Slot::Arms => "Arms",
+            Slot::Feet => "Feet",
+            Slot::LeftHand => "Left hand",
+            Slot::RightHand => "Right hand",
+        }
+    }
+
+    /// Name of slot used in saved games.
+    pub fn key(&self) -> &'static str {
+        match self {
+            Slot::Head => "head",
+            Slot::Chest => "chest",
+            Slot::Legs => "legs",
+            Slot::Arms => "arms",
+            Slot::Feet => "feet",
+            Slot::LeftHand => "left_hand",
+            Slot::RightHand => "right_hand",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Slot> {
+        Slot::ALL.iter().copied().find(|slot| slot.key() == key)
+    }
+}
+
+/// Player statistic shown on character sheet.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Attribute {
+    Health,
+    Mana,
+    Attack,
+    Defense,
+    Strength,
+    Dexterity,
+    Intelligence,
+    Moves,
+}
+
+impl Attribute {
+    pub const ALL: [Attribute; 8] = [
+        Attribute::Health,
+        Attribute::Mana,
+        Attribute::Attack,
+        Attribute::Defense,
+        Attribute::Strength,
+        Attribute::Dexterity,
+        Attribute::Intelligence,
+        Attribute::Moves,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Attribute::Health => "Health",
+            Attribute::Mana => "Mana",
+            Attribute::Attack => "Attack",
+            Attribute::Defense => "Defense",
+            Attribute::Strength => "Strength",
+            Attribute::Dexterity => "Dexterity",
+            Attribute::Intelligence => "Intelligence",
+            Attribute::Moves => "Moves",
+        }
+    }
+
+    /// Modifier of this attribute given by item.
+    fn modifier(&self, item: &Item) -> i8 {
+        match self {
+            Attribute::Health => item.health_mod,
+            Attribute::Mana => item.mana_mod,
+            Attribute::Attack => item.attack_mod,
+            Attribute::Defense => item.defense_mod,
+            Attribute::Strength => item.strength_mod,
+            Attribute::Dexterity => item.dexterity_mod,
+            Attribute::Intelligence => item.intelligence_mod,
+            Attribute::Moves => item.speed_mod,
+        }
+    }
+}
+
+/// Parts from which attribute value is computed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Breakdown {
+    pub base: u8,
+    pub level: u8,
+    /// Modifiers of items in every slot, empty slots give 0.
+    pub items: Vec<(Slot, i8)>,
+    pub total: u8,
+}
 
 pub struct Player {
     name: String,
@@ -14,6 +132,9 @@ pub struct Player {
     intelligence: u8,
     speed: u8,
     remaining_moves: u8,
+    can_swim: bool,
+    has_boat: bool,
+    gold: u32,
     head: Option<Item>,
     chest: Option<Item>,
     legs: Option<Item>,
@@ -48,6 +169,9 @@ impl Player {
             intelligence,
             speed,
             remaining_moves: speed,
+            can_swim: false,
+            has_boat: false,
+            gold: 0,
             head: None,
             chest: None,
             legs: None,
@@ -77,6 +201,43 @@ impl Player {
         }
     }
 
+    /// Damage which ignores player defense, like burns or falls.
+    pub fn take_damage(&mut self, damage: u8) {
+        self.current_health = self.current_health.saturating_sub(damage);
+    }
+
+    /// Sets current health, which can't be higher than maximum.
+    pub fn set_health(&mut self, health: u8) {
+        self.current_health = health.min(self.max_health());
+    }
+
+    pub fn heal(&mut self, amount: u8) {
+        self.set_health(self.current_health.saturating_add(amount));
+    }
+
+    /// Permanently raises base value of the stat.
+    pub fn raise_stat(&mut self, stat: Stat, amount: u8) {
+        self.set_base_stat(stat, self.base_stat(stat).saturating_add(amount));
+    }
+
+    /// Value of the stat without level and equipment bonuses.
+    pub fn base_stat(&self, stat: Stat) -> u8 {
+        match stat {
+            Stat::Strength => self.strenght,
+            Stat::Dexterity => self.dexterity,
+            Stat::Intelligence => self.intelligence,
+        }
+    }
+
+    /// Sets base value of the stat, e.g. read from saved game.
+    pub fn set_base_stat(&mut self, stat: Stat, value: u8) {
+        match stat {
+            Stat::Strength => self.strenght = value,
+            Stat::Dexterity => self.dexterity = value,
+            Stat::Intelligence => self.intelligence = value,
+        }
+    }
+
     pub fn is_dead(&self) -> bool {
         self.current_health == 0
     }
@@ -97,6 +258,38 @@ impl Player {
         self.position += vec;
     }
 
+    pub fn mobility(&self) -> Mobility {
+        Mobility {
+            swim: self.can_swim,
+            boat: self.has_boat,
+        }
+    }
+
+    pub fn learn_swimming(&mut self) {
+        self.can_swim = true;
+    }
+
+    pub fn set_boat(&mut self, has_boat: bool) {
+        self.has_boat = has_boat;
+    }
+
+    pub fn gold(&self) -> u32 {
+        self.gold
+    }
+
+    pub fn add_gold(&mut self, gold: u32) {
+        self.gold = self.gold.saturating_add(gold);
+    }
+
+    /// Takes gold from player. Returns false, leaving gold untouched, if there is not enough.
+    pub fn spend_gold(&mut self, gold: u32) -> bool {
+        if gold > self.gold {
+            return false;
+        }
+        self.gold -= gold;
+        true
+    }
+
     pub fn level(&self) -> u8 {
         self.level
     }
@@ -106,7 +299,23 @@ impl Player {
     }
 
     pub fn next_level_xp(&self) -> u8 {
-        0
+        (self.level + 1).saturating_mul(XP_PER_LEVEL)
+    }
+
+    /// Adds experience and raises level every time enough of it is gathered.
+    pub fn add_xp(&mut self, xp: u8) {
+        let mut xp = self.current_xp as u32 + xp as u32;
+        while xp >= self.next_level_xp() as u32 && self.level < u8::MAX {
+            xp -= self.next_level_xp() as u32;
+            self.level += 1;
+        }
+        self.current_xp = xp.min(u8::MAX as u32) as u8;
+    }
+
+    /// Sets level and experience, e.g. read from saved game.
+    pub fn set_experience(&mut self, level: u8, xp: u8) {
+        self.level = level;
+        self.current_xp = xp;
     }
 
     pub fn max_health(&self) -> u8 {
@@ -195,6 +404,11 @@ impl Player {
         self.current_mana
     }
 
+    /// Sets current mana, which can't be higher than maximum.
+    pub fn set_mana(&mut self, mana: u8) {
+        self.current_mana = mana.min(self.max_mana());
+    }
+
     pub fn attack(&self) -> u8 {
         let mut attack = 0;
         let static_attack = (self.strenght + self.level) as i8;
@@ -437,30 +651,60 @@ impl Player {
         self.remaining_moves
     }
 
-    pub fn head(&self) -> &Option<Item> {
-        &self.head
-    }
-
-    pub fn chest(&self) -> &Option<Item> {
-        &self.chest
-    }
-
-    pub fn legs(&self) -> &Option<Item> {
-        &self.legs
-    }
-
-    pub fn arms(&self) -> &Option<Item> {
-        &self.arms
-    }
-
-    pub fn foots(&self) -> &Option<Item> {
-        &self.foots
-    }
-    pub fn left_hand(&self) -> &Option<Item> {
-        &self.left_hand
-    }
-    pub fn right_hand(&self) -> &Option<Item> {
-        &self.right_hand
+    pub fn equipped(&self, slot: Slot) -> Option<&Item> {
+        match slot {
+            Slot::Head => self.head.as_ref(),
+            Slot::Chest => self.chest.as_ref(),
+            Slot::Legs => self.legs.as_ref(),
+            Slot::Arms => self.arms.as_ref(),
+            Slot::Feet => self.foots.as_ref(),
+            Slot::LeftHand => self.left_hand.as_ref(),
+            Slot::RightHand => self.right_hand.as_ref(),
+        }
+    }
+
+    /// Puts item in slot and returns item, which was equipped there before.
+    pub fn equip(&mut self, slot: Slot, item: Option<Item>) -> Option<Item> {
+        let equipped = match slot {
+            Slot::Head => &mut self.head,
+            Slot::Chest => &mut self.chest,
+            Slot::Legs => &mut self.legs,
+            Slot::Arms => &mut self.arms,
+            Slot::Feet => &mut self.foots,
+            Slot::LeftHand => &mut self.left_hand,
+            Slot::RightHand => &mut self.right_hand,
+        };
+        std::mem::replace(equipped, item)
+    }
+
+    /// Shows how base value, level and equipped items make value of attribute.
+    pub fn breakdown(&self, attribute: Attribute) -> Breakdown {
+        let (base, total) = match attribute {
+            Attribute::Health => (self.max_health, self.max_health()),
+            Attribute::Mana => (self.max_mana, self.max_mana()),
+            Attribute::Attack => (self.strenght, self.attack()),
+            Attribute::Defense => (self.dexterity, self.defense()),
+            Attribute::Strength => (self.strenght, self.strenght()),
+            Attribute::Dexterity => (self.dexterity, self.dexterity()),
+            Attribute::Intelligence => (self.intelligence, self.intelligence()),
+            Attribute::Moves => (self.speed, self.max_moves()),
+        };
+        let items = Slot::ALL
+            .iter()
+            .map(|slot| {
+                let modifier = self
+                    .equipped(*slot)
+                    .map_or(0, |item| attribute.modifier(item));
+                (*slot, modifier)
+            })
+            .collect();
+
+        Breakdown {
+            base,
+            level: self.level,
+            items,
+            total,
+        }
     }
 
     pub fn inventory(&self) -> &Vec<Item> {
@@ -477,3 +721,26 @@ impl Player {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn breakdown_sums_to_total() {
+        let mut player = Player::new("Tester".to_string(), 10, 10, 5, 4, 7, 6);
+        player.head = Item::from_code("gear head 2 0 -1 0 1 0 0 0");
+        player.add_xp(10);
+
+        for attribute in Attribute::ALL.iter() {
+            let breakdown = player.breakdown(*attribute);
+            let items: i32 = breakdown.items.iter().map(|(_, value)| *value as i32).sum();
+            let total = breakdown.base as i32 + breakdown.level as i32 + items;
+            assert_eq!(total.max(0) as u8, breakdown.total, "{:?}", attribute);
+        }
+
+        let health = player.breakdown(Attribute::Health);
+        assert_eq!((health.base, health.level, health.total), (10, 1, 13));
+        assert_eq!(health.items[0], (Slot::Head, 2));
+    }
+}
@@ -0,0 +1,507 @@
+use crate::{
+    enemy,
+    flags::{FlagValue, Flags},
+    item::Item,
+    player::Player,
+    vector::Vec2,
+};
+use std::{
+    collections::HashMap,
+    fs,
+    io::{self, ErrorKind},
+    sync::OnceLock,
+};
+
+pub const QUESTS_FILE: &str = "data/quests.def";
+
+/// Saved progress of quest which is already completed.
+const DONE: &str = "done";
+
+static QUESTS: OnceLock<Quests> = OnceLock::new();
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Objective {
+    /// Defeat given number of enemies of one kind.
+    Kill(String, u32),
+    /// Have item in inventory.
+    Fetch(Item),
+    Reach(Vec2),
+    /// Talk with NPC using given dialogue.
+    Talk(String),
+    /// World state flag is set, or has given value.
+    Flag(String, Option<FlagValue>),
+}
+
+impl Objective {
+    /// Progress needed to fulfil the objective.
+    pub fn goal(&self) -> u32 {
+        match self {
+            Objective::Kill(_, count) => *count,
+            _ => 1,
+        }
+    }
+
+    fn parse(text: &str) -> Result<Self, String> {
+        let parts: Vec<&str> = text.split_whitespace().collect();
+        match parts.as_slice() {
+            ["kill", kind, _] if !enemy::KINDS.contains(kind) => {
+                Err(format!("unknown enemy '{}'", kind))
+            }
+            ["kill", kind, count] => match count.parse() {
+                Ok(count) if count > 0 => Ok(Objective::Kill(kind.to_string(), count)),
+                _ => Err(format!("invalid count '{}'", count)),
+            },
+            ["fetch", code @ ..] => Item::from_code(&code.join(" "))
+                .map(Objective::Fetch)
+                .ok_or(format!("unknown item '{}'", code.join(" "))),
+            ["reach", position] => {
+                let (x, y) = position
+                    .split_once(',')
+                    .ok_or(format!("invalid position '{}'", position))?;
+                match (x.parse(), y.parse()) {
+                    (Ok(x), Ok(y)) => Ok(Objective::Reach(Vec2::new(x, y))),
+                    _ => Err(format!("invalid position '{}'", position)),
+                }
+            }
+            ["talk", dialogue] => Ok(Objective::Talk(dialogue.to_string())),
+            ["flag", name] => Ok(Objective::Flag(name.to_string(), None)),
+            ["flag", name, value @ ..] => Ok(Objective::Flag(
+                name.to_string(),
+                Some(FlagValue::parse(&value.join(" "))),
+            )),
+            _ => Err(format!("invalid objective '{}'", text)),
+        }
+    }
+
+    fn describe(&self, progress: u32) -> String {
+        match self {
+            Objective::Kill(kind, count) => {
+                format!("Defeat {} {}: {}/{}", count, kind, progress, count)
+            }
+            Objective::Fetch(item) => format!("Bring {}", item),
+            Objective::Reach(position) => format!("Reach {},{}", position.x, position.y),
+            Objective::Talk(dialogue) => format!("Talk with {}", dialogue),
+            Objective::Flag(name, _) => capitalize_words(name),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Reward {
+    Xp(u8),
+    Gold(u32),
+    Item(Item),
+    Flag(String, FlagValue),
+}
+
+impl Reward {
+    fn parse(text: &str) -> Result<Self, String> {
+        let (name, value) = text
+            .split_once(' ')
+            .ok_or(format!("invalid reward '{}'", text))?;
+        let value = value.trim();
+        let error = || format!("invalid reward '{}'", text);
+
+        match name {
+            "xp" => value.parse().map(Reward::Xp).map_err(|_| error()),
+            "gold" => value.parse().map(Reward::Gold).map_err(|_| error()),
+            "item" => Item::from_code(value).map(Reward::Item).ok_or_else(error),
+            "flag" => match value.split_once(' ') {
+                Some((name, value)) => Ok(Reward::Flag(name.to_string(), FlagValue::parse(value))),
+                None => Ok(Reward::Flag(value.to_string(), FlagValue::Bool(true))),
+            },
+            _ => Err(error()),
+        }
+    }
+}
+
+pub struct Quest {
+    pub title: String,
+    pub description: String,
+    pub objectives: Vec<Objective>,
+    pub rewards: Vec<Reward>,
+}
+
+/// Something which happened in game and may move quests forward.
+pub enum QuestEvent<'a> {
+    Killed(&'a str),
+    Moved(Vec2),
+    Talked(&'a str),
+    /// Player inventory changed, so fetch objectives are checked again.
+    InventoryChanged,
+    FlagsChanged,
+}
+
+/// Quest taken by player with progress of every objective.
+#[derive(Debug, Clone, PartialEq)]
+struct QuestProgress {
+    id: String,
+    progress: Vec<u32>,
+    completed: bool,
+}
+
+/// Quests taken by player.
+#[derive(Debug, Default)]
+pub struct Journal {
+    quests: Vec<QuestProgress>,
+}
+
+impl Journal {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds quest to journal. Returns false when quest is unknown or was taken before.
+    pub fn start(&mut self, id: &str) -> bool {
+        let quest = match quests().get(id) {
+            Some(quest) => quest,
+            None => return false,
+        };
+        if self.quests.iter().any(|taken| taken.id == id) {
+            return false;
+        }
+
+        self.quests.push(QuestProgress {
+            id: id.to_string(),
+            progress: vec![0; quest.objectives.len()],
+            completed: false,
+        });
+        true
+    }
+
+    pub fn is_active(&self, id: &str) -> bool {
+        self.quests
+            .iter()
+            .any(|quest| quest.id == id && !quest.completed)
+    }
+
+    pub fn is_completed(&self, id: &str) -> bool {
+        self.quests
+            .iter()
+            .any(|quest| quest.id == id && quest.completed)
+    }
+
+    /// Updates progress of active quests. Returns ids of quests completed by the event,
+    /// their rewards have to be given by caller.
+    pub fn update(&mut self, event: &QuestEvent, player: &Player, flags: &Flags) -> Vec<String> {
+        let mut completed = Vec::new();
+
+        for taken in self.quests.iter_mut().filter(|quest| !quest.completed) {
+            let quest = match quests().get(&taken.id) {
+                Some(quest) => quest,
+                None => continue,
+            };
+
+            for (objective, progress) in quest.objectives.iter().zip(taken.progress.iter_mut()) {
+                match (objective, event) {
+                    (Objective::Kill(kind, _), QuestEvent::Killed(killed)) if kind == killed => {
+                        *progress += 1
+                    }
+                    (Objective::Reach(target), QuestEvent::Moved(position))
+                        if target == position =>
+                    {
+                        *progress = 1
+                    }
+                    (Objective::Talk(target), QuestEvent::Talked(dialogue))
+                        if target == dialogue =>
+                    {
+                        *progress = 1
+                    }
+                    // Item may be sold or used, so fetch progress follows inventory.
+                    (Objective::Fetch(item), _) => {
+                        *progress = player.inventory().contains(item) as u32
+                    }
+                    (Objective::Flag(name, value), _) => {
+                        let met = match value {
+                            Some(value) => flags.get(name) == Some(value),
+                            None => flags.is_set(name),
+                        };
+                        *progress = met as u32
+                    }
+                    _ => (),
+                }
+                *progress = (*progress).min(objective.goal());
+            }
+
+            let done = quest
+                .objectives
+                .iter()
+                .zip(taken.progress.iter())
+                .all(|(objective, progress)| *progress >= objective.goal());
+            if done {
+                taken.completed = true;
+                completed.push(taken.id.clone());
+            }
+        }
+
+        completed
+    }
+
+    /// Journal lines of active quests: title followed by description and objectives.
+    pub fn active_entries(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        for taken in self.quests.iter().filter(|quest| !quest.completed) {
+            if let Some(quest) = quests().get(&taken.id) {
+                lines.push(quest.title.clone());
+                lines.push(format!("  {}", quest.description));
+                for (objective, progress) in quest.objectives.iter().zip(taken.progress.iter()) {
+                    let mark = if *progress >= objective.goal() {
+                        'x'
+                    } else {
+                        ' '
+                    };
+                    lines.push(format!("  [{}] {}", mark, objective.describe(*progress)));
+                }
+            }
+        }
+        lines
+    }
+
+    /// Titles of completed quests.
+    pub fn completed_entries(&self) -> Vec<String> {
+        self.quests
+            .iter()
+            .filter(|quest| quest.completed)
+            .filter_map(|taken| quests().get(&taken.id))
+            .map(|quest| quest.title.clone())
+            .collect()
+    }
+
+    /// Writes journal as lines in format: `id = progress, progress` or `id = done`.
+    pub fn to_lines(&self) -> Vec<String> {
+        self.quests
+            .iter()
+            .map(|quest| {
+                let progress = if quest.completed {
+                    DONE.to_string()
+                } else {
+                    let progress: Vec<String> = quest
+                        .progress
+                        .iter()
+                        .map(|value| value.to_string())
+                        .collect();
+                    progress.join(", ")
+                };
+                format!("{} = {}", quest.id, progress)
+            })
+            .collect()
+    }
+
+    /// Reads back line written by `to_lines`.
+    pub fn parse_line(&mut self, line: &str) -> Result<(), String> {
+        let (id, progress) = line.split_once('=').ok_or("missing '=' in quest")?;
+        let (id, progress) = (id.trim(), progress.trim());
+        let quest = quests().get(id).ok_or(format!("unknown quest '{}'", id))?;
+
+        let (progress, completed) = if progress == DONE {
+            (quest.objectives.iter().map(Objective::goal).collect(), true)
+        } else {
+            let progress: Vec<u32> = progress
+                .split(',')
+                .map(|value| value.trim().parse().ok())
+                .collect::<Option<_>>()
+                .ok_or(format!("invalid progress of quest '{}'", id))?;
+            if progress.len() != quest.objectives.len() {
+                return Err(format!("invalid progress of quest '{}'", id));
+            }
+            (progress, false)
+        };
+
+        self.quests.retain(|quest| quest.id != id);
+        self.quests.push(QuestProgress {
+            id: id.to_string(),
+            progress,
+            completed,
+        });
+        Ok(())
+    }
+}
+
+/// Turns flag name like `boss_killed` into text shown in journal: `Boss killed`.
+fn capitalize_words(name: &str) -> String {
+    let text = name.replace('_', " ");
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+pub struct Quests {
+    quests: HashMap<String, Quest>,
+}
+
+/// Quests started by dialogue effects and tracked in journal. Without `init`, bundled
+/// quests file is used, which is what tests rely on.
+pub fn quests() -> &'static Quests {
+    QUESTS.get_or_init(|| {
+        Quests::load_from_file(QUESTS_FILE)
+            .unwrap_or_else(|error| panic!("{}: {}", QUESTS_FILE, error))
+    })
+}
+
+/// Sets quests used by the game. It can be done only once, before first `quests`.
+pub fn init(quests: Quests) -> bool {
+    QUESTS.set(quests).is_ok()
+}
+
+impl Quests {
+    pub fn load_from_file(file_name: &str) -> io::Result<Self> {
+        let raw_data = fs::read_to_string(file_name)?;
+        Self::parse(&raw_data).map_err(|error| io::Error::new(ErrorKind::InvalidData, error))
+    }
+
+    pub fn get(&self, id: &str) -> Option<&Quest> {
+        self.quests.get(id)
+    }
+
+    fn parse(raw_data: &str) -> Result<Self, String> {
+        let mut quests = HashMap::new();
+        let mut current: Option<String> = None;
+
+        for (idx, line) in raw_data.lines().enumerate() {
+            let line_number = idx + 1;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with(';') {
+                continue;
+            }
+            let error = |error: String| format!("line {}: {}", line_number, error);
+
+            if line.starts_with('[') && line.ends_with(']') {
+                let id = line[1..line.len() - 1].trim().to_string();
+                if quests.contains_key(&id) {
+                    return Err(error(format!("duplicate quest '{}'", id)));
+                }
+                quests.insert(
+                    id.clone(),
+                    Quest {
+                        title: id.clone(),
+                        description: String::new(),
+                        objectives: Vec::new(),
+                        rewards: Vec::new(),
+                    },
+                );
+                current = Some(id);
+                continue;
+            }
+
+            let quest = match &current {
+                Some(id) => quests.get_mut(id).unwrap(),
+                None => return Err(error("text outside of section".to_string())),
+            };
+
+            let (name, value) = line
+                .split_once('=')
+                .ok_or_else(|| error("missing '='".to_string()))?;
+            let value = value.trim();
+
+            match name.trim() {
+                "title" => quest.title = value.to_string(),
+                "description" => quest.description = value.to_string(),
+                "objective" => quest
+                    .objectives
+                    .push(Objective::parse(value).map_err(error)?),
+                "reward" => quest.rewards.push(Reward::parse(value).map_err(error)?),
+                other => return Err(error(format!("unknown property '{}'", other))),
+            }
+        }
+
+        if let Some((id, _)) = quests.iter().find(|(_, quest)| quest.objectives.is_empty()) {
+            return Err(format!("quest '{}' has no objectives", id));
+        }
+
+        Ok(Self { quests })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quest_progress_and_saving() {
+        let player = Player::new("Tester".to_string(), 10, 10, 5, 4, 7, 6);
+        let flags = Flags::new();
+        let mut journal = Journal::new();
+        assert!(journal.start("cave_rats"));
+        assert!(!journal.start("cave_rats"));
+        assert!(!journal.start("no_such_quest"));
+
+        assert!(journal
+            .update(&QuestEvent::Killed("goblin"), &player, &flags)
+            .is_empty());
+        assert!(journal
+            .update(&QuestEvent::Killed("rat"), &player, &flags)
+            .is_empty());
+        assert_eq!(journal.to_lines(), vec!["cave_rats = 1, 0".to_string()]);
+
+        let mut loaded = Journal::new();
+        loaded.parse_line("cave_rats = 1, 0").unwrap();
+        assert!(loaded.is_active("cave_rats"));
+        for _ in 0..3 {
+            loaded.update(&QuestEvent::Killed("rat"), &player, &flags);
+        }
+        assert_eq!(loaded.to_lines(), vec!["cave_rats = 3, 0".to_string()]);
+        assert_eq!(
+            loaded.update(&QuestEvent::Talked("hermit"), &player, &flags),
+            vec!["cave_rats".to_string()]
+        );
+        assert!(loaded.is_completed("cave_rats"));
+        assert_eq!(loaded.to_lines(), vec!["cave_rats = done".to_string()]);
+        assert!(loaded.parse_line("cave_rats = 1").is_err());
+    }
+
+    #[test]
+    fn quest_needs_valid_objectives() {
+        assert_eq!(
+            Quests::parse("[q]\ntitle = Q\n").err(),
+            Some("quest 'q' has no objectives".to_string())
+        );
+        assert_eq!(
+            Quests::parse("[q]\nobjective = kill rat 0\n").err(),
+            Some("line 2: invalid count '0'".to_string())
+        );
+        assert_eq!(
+            Quests::parse("[q]\nobjective = reach north\n").err(),
+            Some("line 2: invalid position 'north'".to_string())
+        );
+        assert_eq!(
+            Quests::parse("[q]\nobjective = kill dragon 1\n").err(),
+            Some("line 2: unknown enemy 'dragon'".to_string())
+        );
+    }
+
+    #[test]
+    fn quest_ids_are_unique() {
+        let data = "[q]\nobjective = kill rat 1\n\n[q]\nobjective = talk hermit\n";
+        assert_eq!(
+            Quests::parse(data).err(),
+            Some("line 4: duplicate quest 'q'".to_string())
+        );
+    }
+
+    #[test]
+    fn rewards_are_checked() {
+        assert_eq!(
+            Quests::parse("[q]\nreward = xp lots\nobjective = talk hermit\n").err(),
+            Some("line 2: invalid reward 'xp lots'".to_string())
+        );
+        let quests = Quests::parse("[q]\nobjective = flag done\nreward = flag door open\n");
+        assert_eq!(
+            quests.unwrap().get("q").unwrap().rewards,
+            vec![Reward::Flag("door".to_string(), FlagValue::parse("open"))]
+        );
+    }
+
+    #[test]
+    fn bundled_quests_talk_with_existing_dialogues() {
+        let quests = Quests::load_from_file(QUESTS_FILE).unwrap();
+        for quest in quests.quests.values() {
+            for objective in quest.objectives.iter() {
+                if let Objective::Talk(dialogue) = objective {
+                    assert!(crate::dialogue::dialogues().get(dialogue).is_some());
+                }
+            }
+        }
+    }
+}
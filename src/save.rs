@@ -0,0 +1,271 @@
+use crate::{
+    dialogue::Stat,
+    flags::Flags,
+    item::Item,
+    map::Map,
+    player::{Player, Slot},
+    quest::Journal,
+    vector::Vec2,
+};
+use std::{
+    fs::{self, File},
+    io::{self, BufWriter, ErrorKind, Write},
+    path::Path,
+};
+
+pub const QUICKSAVE_FILE: &str = "data/Saves/quicksave.map";
+
+const PLAYER_SECTION: &str = "[player]";
+const QUESTS_SECTION: &str = "[quests]";
+const FLAGS_SECTION: &str = "[flags]";
+/// Base stats of player, which can be raised by scripts, with their property names.
+const STATS: [(&str, Stat); 3] = [
+    ("strength", Stat::Strength),
+    ("dexterity", Stat::Dexterity),
+    ("intelligence", Stat::Intelligence),
+];
+
+/// Saves game as map file with current tiles, signs, containers and revealed traps, followed
+/// by player, quests and flags sections. Saved game is loaded like any other map.
+pub fn save_game(
+    file_name: &str,
+    map: &Map,
+    player: &Player,
+    journal: &Journal,
+    flags: &Flags,
+) -> io::Result<()> {
+    if let Some(dir) = Path::new(file_name).parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let inventory: Vec<String> = player.inventory().iter().map(Item::to_code).collect();
+
+    let mut writer = BufWriter::new(File::create(file_name)?);
+    writer.write_all(map.to_map_data().as_bytes())?;
+    writeln!(writer)?;
+    writeln!(writer, "{}", PLAYER_SECTION)?;
+    writeln!(
+        writer,
+        "position = {},{}",
+        player.position().x,
+        player.position().y
+    )?;
+    writeln!(writer, "health = {}", player.current_health())?;
+    writeln!(writer, "mana = {}", player.current_mana())?;
+    writeln!(writer, "level = {}", player.level())?;
+    writeln!(writer, "xp = {}", player.current_xp())?;
+    writeln!(writer, "gold = {}", player.gold())?;
+    for (name, stat) in STATS.iter() {
+        writeln!(writer, "{} = {}", name, player.base_stat(*stat))?;
+    }
+    writeln!(writer, "inventory = {}", inventory.join(", "))?;
+    writeln!(writer, "swimming = {}", player.mobility().swim)?;
+    writeln!(writer, "boat = {}", player.mobility().boat)?;
+    for slot in Slot::ALL.iter() {
+        if let Some(item) = player.equipped(*slot) {
+            writeln!(writer, "{} = {}", slot.key(), item.to_code())?;
+        }
+    }
+
+    writeln!(writer)?;
+    writeln!(writer, "{}", QUESTS_SECTION)?;
+    for line in journal.to_lines() {
+        writeln!(writer, "{}", line)?;
+    }
+
+    writeln!(writer)?;
+    writeln!(writer, "{}", FLAGS_SECTION)?;
+    for line in flags.to_lines() {
+        writeln!(writer, "{}", line)?;
+    }
+    writer.flush()
+}
+
+/// Restores player state from map file. Maps without player section leave player unchanged.
+pub fn load_player(file_name: &str, player: &mut Player) -> io::Result<()> {
+    let raw_data = fs::read_to_string(file_name)?;
+    parse_player(&raw_data, player).map_err(|error| io::Error::new(ErrorKind::InvalidData, error))
+}
+
+/// Restores quests taken in saved game.
+pub fn load_journal(file_name: &str, journal: &mut Journal) -> io::Result<()> {
+    let raw_data = fs::read_to_string(file_name)?;
+    let loaded =
+        Map::section_lines(&raw_data, QUESTS_SECTION).try_for_each(|line| journal.parse_line(line));
+    loaded.map_err(|error| io::Error::new(ErrorKind::InvalidData, error))
+}
+
+/// Restores world state flags of saved game.
+pub fn load_flags(file_name: &str, flags: &mut Flags) -> io::Result<()> {
+    let raw_data = fs::read_to_string(file_name)?;
+    let loaded =
+        Map::section_lines(&raw_data, FLAGS_SECTION).try_for_each(|line| flags.parse_line(line));
+    loaded.map_err(|error| io::Error::new(ErrorKind::InvalidData, error))
+}
+
+fn parse_player(raw_data: &str, player: &mut Player) -> Result<(), String> {
+    let mut health = None;
+    let mut mana = None;
+    for line in Map::section_lines(raw_data, PLAYER_SECTION) {
+        let mut parts = line.splitn(2, '=');
+        let name = parts.next().unwrap_or("").trim();
+        let value = parts.next().ok_or("missing '=' in player section")?.trim();
+
+        match name {
+            "position" => {
+                let coords: Vec<Option<i32>> =
+                    value.split(',').map(|c| c.trim().parse().ok()).collect();
+                match coords.as_slice() {
+                    [Some(x), Some(y)] => player.set_position(Vec2::new(*x, *y)),
+                    _ => return Err(format!("invalid player position '{}'", value)),
+                }
+            }
+            "health" => {
+                let parsed = value
+                    .parse()
+                    .map_err(|_| format!("invalid player health '{}'", value))?;
+                health = Some(parsed);
+            }
+            "mana" => {
+                let parsed = value
+                    .parse()
+                    .map_err(|_| format!("invalid player mana '{}'", value))?;
+                mana = Some(parsed);
+            }
+            "level" => {
+                let level = value
+                    .parse()
+                    .map_err(|_| format!("invalid player level '{}'", value))?;
+                player.set_experience(level, player.current_xp());
+            }
+            "xp" => {
+                let xp = value
+                    .parse()
+                    .map_err(|_| format!("invalid player xp '{}'", value))?;
+                player.set_experience(player.level(), xp);
+            }
+            "gold" => {
+                let gold = value
+                    .parse()
+                    .map_err(|_| format!("invalid player gold '{}'", value))?;
+                player.add_gold(gold);
+            }
+            "swimming" => match value {
+                "true" => player.learn_swimming(),
+                "false" => {}
+                _ => return Err(format!("invalid player swimming '{}'", value)),
+            },
+            "boat" => {
+                let boat = value
+                    .parse()
+                    .map_err(|_| format!("invalid player boat '{}'", value))?;
+                player.set_boat(boat);
+            }
+            "inventory" => {
+                for code in value.split(',').map(|code| code.trim()) {
+                    if !code.is_empty() {
+                        let item =
+                            Item::from_code(code).ok_or(format!("unknown item '{}'", code))?;
+                        player.add_to_inventory(item);
+                    }
+                }
+            }
+            _ => {
+                if let Some((_, stat)) = STATS.iter().find(|(stat_name, _)| *stat_name == name) {
+                    let value = value
+                        .parse()
+                        .map_err(|_| format!("invalid player {} '{}'", name, value))?;
+                    player.set_base_stat(*stat, value);
+                } else if let Some(slot) = Slot::from_key(name) {
+                    let item = Item::from_code(value).ok_or(format!("unknown item '{}'", value))?;
+                    player.equip(slot, Some(item));
+                } else {
+                    return Err(format!("unknown player property '{}'", name));
+                }
+            }
+        }
+    }
+
+    // Maximum health and mana depend on level and equipment, so they are set after them.
+    if let Some(health) = health {
+        player.set_health(health);
+    }
+    if let Some(mana) = mana {
+        player.set_mana(mana);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{map::MapTile, serialize::Serialize};
+
+    #[test]
+    fn saved_game_restores_map_and_player() {
+        let file_name = std::env::temp_dir().join("text_adv_save_test.map");
+        let file_name = file_name.to_str().unwrap();
+
+        let mut map = Map::load_from_file("data/Maps/Test.map").unwrap();
+        let chest = Vec2::new(19, 4);
+        map.container_mut(chest).unwrap().unlock();
+        let items = map.container(chest).unwrap().items().to_vec();
+        let trap = Vec2::new(14, 3);
+        map.reveal_trap(trap);
+
+        let mut player = Player::new("Tester".to_string(), 10, 10, 5, 4, 7, 6);
+        player.set_position(Vec2::new(3, 3));
+        player.add_to_inventory(Item::lockpick());
+        player.add_gold(12);
+        player.add_xp(14);
+        player.learn_swimming();
+        let helmet = Item::from_code("gear head 2 0 -1 0 1 0 0 0").unwrap();
+        player.equip(Slot::Head, Some(helmet.clone()));
+        // Level and helmet raise health above maximum of new player.
+        player.set_health(12);
+        assert!(Player::new("Tester".to_string(), 10, 10, 5, 4, 7, 6).max_health() < 12);
+        // Shrine raised intelligence and some mana was used.
+        player.raise_stat(Stat::Intelligence, 1);
+        player.set_mana(3);
+        let mut journal = Journal::new();
+        journal.start("lost_key");
+        let mut flags = Flags::new();
+        flags.add("rats_killed", 2);
+        save_game(file_name, &map, &player, &journal, &flags).unwrap();
+
+        let loaded = Map::load_from_file(file_name).unwrap();
+        assert!(!loaded.container(chest).unwrap().is_locked());
+        assert_eq!(loaded.container(chest).unwrap().items(), items.as_slice());
+        assert!(matches!(
+            loaded.tile_at(trap),
+            Some(MapTile::Trap { hidden: false, .. })
+        ));
+
+        let mut loaded_player = Player::new("Tester".to_string(), 10, 10, 5, 4, 7, 6);
+        load_player(file_name, &mut loaded_player).unwrap();
+        assert_eq!(loaded_player.position(), Vec2::new(3, 3));
+        assert_eq!(loaded_player.current_health(), 12);
+        assert_eq!(loaded_player.current_mana(), 3);
+        for (_, stat) in STATS.iter() {
+            assert_eq!(loaded_player.base_stat(*stat), player.base_stat(*stat));
+        }
+        assert_eq!(loaded_player.base_stat(Stat::Intelligence), 8);
+        assert_eq!(loaded_player.gold(), 12);
+        assert_eq!((loaded_player.level(), loaded_player.current_xp()), (1, 4));
+        assert_eq!(loaded_player.mobility(), player.mobility());
+        assert_eq!(loaded_player.inventory(), &vec![Item::lockpick()]);
+        assert_eq!(loaded_player.equipped(Slot::Head), Some(&helmet));
+        assert_eq!(loaded_player.equipped(Slot::Chest), None);
+
+        let mut loaded_journal = Journal::new();
+        load_journal(file_name, &mut loaded_journal).unwrap();
+        assert!(loaded_journal.is_active("lost_key"));
+
+        let mut loaded_flags = Flags::new();
+        load_flags(file_name, &mut loaded_flags).unwrap();
+        assert_eq!(loaded_flags.to_lines(), flags.to_lines());
+
+        fs::remove_file(file_name).unwrap();
+    }
+}
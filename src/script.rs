@@ -0,0 +1,453 @@
+use crate::{
+    dialogue::{Condition, Effect, Stat},
+    map::registry,
+    vector::Vec2,
+};
+use std::{
+    collections::HashMap,
+    fs,
+    io::{self, ErrorKind},
+    sync::OnceLock,
+};
+
+pub const SCRIPTS_FILE: &str = "data/scripts.def";
+
+/// Prefix of scripts run when item is used, followed by item kind, e.g. `on_use.key`.
+pub const ITEM_SCRIPT_PREFIX: &str = "on_use.";
+
+static SCRIPTS: OnceLock<Scripts> = OnceLock::new();
+
+/// Moment in which script attached to map tile is run.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScriptHook {
+    /// Player steps on the tile.
+    Enter,
+    /// Player bumps into the tile.
+    Use,
+    /// Player talks with NPC standing on the tile.
+    Talk,
+}
+
+impl ScriptHook {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "on_enter" => Some(ScriptHook::Enter),
+            "on_use" => Some(ScriptHook::Use),
+            "on_talk" => Some(ScriptHook::Talk),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ScriptHook::Enter => "on_enter",
+            ScriptHook::Use => "on_use",
+            ScriptHook::Talk => "on_talk",
+        }
+    }
+}
+
+/// Everything scripts can do in game. Scripts have no other access to game state.
+pub trait ScriptApi {
+    fn is_met(&self, condition: &Condition) -> bool;
+    /// Applies the same effects, which are used by dialogues.
+    fn apply(&mut self, effect: &Effect);
+    fn message(&mut self, title: &str, text: &str);
+    fn heal(&mut self, amount: u8);
+    fn damage(&mut self, amount: u8);
+    fn add_xp(&mut self, xp: u8);
+    fn raise_stat(&mut self, stat: Stat, amount: u8);
+    fn set_tile(&mut self, position: Vec2, key: &str);
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Statement {
+    Effect(Effect),
+    Message(String, String),
+    Heal(u8),
+    Damage(u8),
+    AddXp(u8),
+    RaiseStat(Stat, u8),
+    SetTile(Vec2, String),
+    /// Ends script.
+    Stop,
+    If {
+        condition: Condition,
+        negated: bool,
+        then: Vec<Statement>,
+        otherwise: Vec<Statement>,
+    },
+}
+
+/// Result of running statements, tells if script was stopped.
+enum Flow {
+    Continue,
+    Stop,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Script {
+    statements: Vec<Statement>,
+}
+
+impl Script {
+    pub fn run(&self, api: &mut impl ScriptApi) {
+        Self::run_statements(&self.statements, api);
+    }
+
+    fn run_statements(statements: &[Statement], api: &mut impl ScriptApi) -> Flow {
+        for statement in statements {
+            match statement {
+                Statement::Effect(effect) => api.apply(effect),
+                Statement::Message(title, text) => api.message(title, text),
+                Statement::Heal(amount) => api.heal(*amount),
+                Statement::Damage(amount) => api.damage(*amount),
+                Statement::AddXp(xp) => api.add_xp(*xp),
+                Statement::RaiseStat(stat, amount) => api.raise_stat(*stat, *amount),
+                Statement::SetTile(position, key) => api.set_tile(*position, key),
+                Statement::Stop => return Flow::Stop,
+                Statement::If {
+                    condition,
+                    negated,
+                    then,
+                    otherwise,
+                } => {
+                    let branch = if api.is_met(condition) != *negated {
+                        then
+                    } else {
+                        otherwise
+                    };
+                    if let Flow::Stop = Self::run_statements(branch, api) {
+                        return Flow::Stop;
+                    }
+                }
+            }
+        }
+
+        Flow::Continue
+    }
+}
+
+/// Splits line into words. Text in quotes is one word, e.g. `message "Gate" "It opens."`.
+fn split_words(line: &str) -> Result<Vec<String>, String> {
+    let mut words = Vec::new();
+    let mut chars = line.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        if ch.is_whitespace() {
+            continue;
+        }
+
+        if ch == '"' {
+            let mut word = String::new();
+            loop {
+                match chars.next() {
+                    Some('"') => break,
+                    Some(ch) => word.push(ch),
+                    None => return Err("missing closing '\"'".to_string()),
+                }
+            }
+            words.push(word);
+        } else {
+            let mut word = ch.to_string();
+            while let Some(ch) = chars.next_if(|ch| !ch.is_whitespace()) {
+                word.push(ch);
+            }
+            words.push(word);
+        }
+    }
+
+    Ok(words)
+}
+
+fn parse_number(value: &str) -> Result<u8, String> {
+    value
+        .parse()
+        .map_err(|_| format!("invalid number '{}'", value))
+}
+
+fn parse_statement(line: &str) -> Result<Statement, String> {
+    let words = split_words(line)?;
+    let words: Vec<&str> = words.iter().map(|word| word.as_str()).collect();
+
+    match words.as_slice() {
+        ["message", title, text] => Ok(Statement::Message(title.to_string(), text.to_string())),
+        ["heal", amount] => Ok(Statement::Heal(parse_number(amount)?)),
+        ["damage", amount] => Ok(Statement::Damage(parse_number(amount)?)),
+        ["add_xp", xp] => Ok(Statement::AddXp(parse_number(xp)?)),
+        ["raise_stat", stat, amount] => {
+            let stat = match *stat {
+                "strength" => Stat::Strength,
+                "dexterity" => Stat::Dexterity,
+                "intelligence" => Stat::Intelligence,
+                _ => return Err(format!("unknown stat '{}'", stat)),
+            };
+            Ok(Statement::RaiseStat(stat, parse_number(amount)?))
+        }
+        ["set_tile", position, key] => {
+            let (x, y) = position
+                .split_once(',')
+                .ok_or(format!("invalid position '{}'", position))?;
+            let position = match (x.parse(), y.parse()) {
+                (Ok(x), Ok(y)) => Vec2::new(x, y),
+                _ => return Err(format!("invalid position '{}'", position)),
+            };
+            if !registry::get().contains(key) {
+                return Err(format!("unknown tile '{}'", key));
+            }
+            Ok(Statement::SetTile(position, key.to_string()))
+        }
+        ["stop"] => Ok(Statement::Stop),
+        // Everything else is effect known from dialogues, like `give_item key`.
+        _ => Effect::parse(line).map(Statement::Effect),
+    }
+}
+
+/// `if` block being parsed: condition, negation and `then` branch, which is set when
+/// `else` is found.
+type OpenIf = (Condition, bool, Option<Vec<Statement>>);
+
+pub struct Scripts {
+    scripts: HashMap<String, Script>,
+}
+
+/// Scripts run by map hooks and used items, looked up by name. The first call reads
+/// scripts file, if none were given to `init`.
+pub fn scripts() -> &'static Scripts {
+    SCRIPTS.get_or_init(|| {
+        Scripts::load_from_file(SCRIPTS_FILE)
+            .unwrap_or_else(|error| panic!("{}: {}", SCRIPTS_FILE, error))
+    })
+}
+
+/// Sets scripts used by the game. It can be done only once, before first `scripts`.
+pub fn init(scripts: Scripts) -> bool {
+    SCRIPTS.set(scripts).is_ok()
+}
+
+impl Scripts {
+    pub fn load_from_file(file_name: &str) -> io::Result<Self> {
+        let raw_data = fs::read_to_string(file_name)?;
+        Self::parse(&raw_data).map_err(|error| io::Error::new(ErrorKind::InvalidData, error))
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Script> {
+        self.scripts.get(name)
+    }
+
+    fn parse(raw_data: &str) -> Result<Self, String> {
+        let mut scripts = HashMap::new();
+        let mut current: Option<String> = None;
+        // Statements of script and of every opened `if` block.
+        let mut blocks: Vec<Vec<Statement>> = Vec::new();
+        let mut conditions: Vec<OpenIf> = Vec::new();
+
+        for (idx, line) in raw_data.lines().enumerate() {
+            let line_number = idx + 1;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with(';') {
+                continue;
+            }
+            let error = |error: String| format!("line {}: {}", line_number, error);
+
+            if line.starts_with('[') && line.ends_with(']') {
+                Self::finish(&mut scripts, current.take(), &mut blocks, &conditions)?;
+                current = Some(line[1..line.len() - 1].trim().to_string());
+                blocks = vec![Vec::new()];
+                continue;
+            }
+
+            if current.is_none() {
+                return Err(error("text outside of section".to_string()));
+            }
+
+            if let Some(condition) = line.strip_prefix("if ") {
+                let (negated, condition) = match condition.trim().strip_prefix("not ") {
+                    Some(condition) => (true, condition),
+                    None => (false, condition),
+                };
+                let condition = Condition::parse(condition.trim()).map_err(error)?;
+                conditions.push((condition, negated, None));
+                blocks.push(Vec::new());
+            } else if line == "else" {
+                match conditions.last_mut() {
+                    Some((_, _, then @ None)) => *then = blocks.pop(),
+                    _ => return Err(error("'else' without 'if'".to_string())),
+                }
+                blocks.push(Vec::new());
+            } else if line == "end" {
+                let (condition, negated, then) = conditions
+                    .pop()
+                    .ok_or_else(|| error("'end' without 'if'".to_string()))?;
+                let last = blocks.pop().unwrap_or_default();
+                let (then, otherwise) = match then {
+                    Some(then) => (then, last),
+                    None => (last, Vec::new()),
+                };
+                blocks.last_mut().unwrap().push(Statement::If {
+                    condition,
+                    negated,
+                    then,
+                    otherwise,
+                });
+            } else {
+                let statement = parse_statement(line).map_err(error)?;
+                blocks.last_mut().unwrap().push(statement);
+            }
+        }
+        Self::finish(&mut scripts, current, &mut blocks, &conditions)?;
+
+        Ok(Self { scripts })
+    }
+
+    /// Stores parsed script, checking that all its `if` blocks were closed.
+    fn finish(
+        scripts: &mut HashMap<String, Script>,
+        name: Option<String>,
+        blocks: &mut Vec<Vec<Statement>>,
+        conditions: &[OpenIf],
+    ) -> Result<(), String> {
+        if let Some(name) = name {
+            if !conditions.is_empty() {
+                return Err(format!("script '{}' has 'if' without 'end'", name));
+            }
+            let statements = blocks.pop().unwrap_or_default();
+            scripts.insert(name, Script { statements });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        flags::{FlagValue, Flags},
+        item::Item,
+        map::{Map, MapTile, Mobility},
+        serialize::Serialize,
+    };
+
+    /// Records what script did.
+    #[derive(Default)]
+    struct Recorder {
+        flags: Flags,
+        messages: Vec<String>,
+        tiles: Vec<(Vec2, String)>,
+    }
+
+    impl ScriptApi for Recorder {
+        fn is_met(&self, condition: &Condition) -> bool {
+            match condition {
+                Condition::Flag(name, None) => self.flags.is_set(name),
+                _ => false,
+            }
+        }
+
+        fn apply(&mut self, effect: &Effect) {
+            if let Effect::SetFlag(name, value) = effect {
+                self.flags.set(name, value.clone());
+            }
+        }
+
+        fn message(&mut self, _title: &str, text: &str) {
+            self.messages.push(text.to_string());
+        }
+
+        fn heal(&mut self, _amount: u8) {}
+        fn damage(&mut self, _amount: u8) {}
+        fn add_xp(&mut self, _xp: u8) {}
+        fn raise_stat(&mut self, _stat: Stat, _amount: u8) {}
+
+        fn set_tile(&mut self, position: Vec2, key: &str) {
+            self.tiles.push((position, key.to_string()));
+        }
+    }
+
+    #[test]
+    fn run_script_with_branches() {
+        let scripts = Scripts::parse(
+            "[lever]\nif flag gate_open\n  message \"Lever\" \"Stuck.\"\n  stop\nelse\n\
+             set_tile 5,3 door_unlocked\nset_flag gate_open\nend\nmessage \"Lever\" \"Click.\"\n",
+        )
+        .unwrap();
+        let lever = scripts.get("lever").unwrap();
+
+        let mut recorder = Recorder::default();
+        lever.run(&mut recorder);
+        lever.run(&mut recorder);
+
+        assert_eq!(recorder.messages, vec!["Click.", "Stuck."]);
+        assert_eq!(
+            recorder.tiles,
+            vec![(Vec2::new(5, 3), "door_unlocked".to_string())]
+        );
+        assert_eq!(
+            recorder.flags.get("gate_open"),
+            Some(&FlagValue::Bool(true))
+        );
+    }
+
+    #[test]
+    fn if_blocks_are_closed() {
+        assert_eq!(
+            Scripts::parse("[s]\nif flag a\nheal 1\n").err(),
+            Some("script 's' has 'if' without 'end'".to_string())
+        );
+        assert_eq!(
+            Scripts::parse("[s]\nend\n").err(),
+            Some("line 2: 'end' without 'if'".to_string())
+        );
+        assert_eq!(
+            Scripts::parse("[s]\nelse\n").err(),
+            Some("line 2: 'else' without 'if'".to_string())
+        );
+    }
+
+    #[test]
+    fn statement_arguments_are_checked() {
+        assert_eq!(
+            Scripts::parse("[s]\nset_tile 1,1 marble\n").err(),
+            Some("line 2: unknown tile 'marble'".to_string())
+        );
+        assert_eq!(
+            Scripts::parse("[s]\nmessage \"Oops\n").err(),
+            Some("line 2: missing closing '\"'".to_string())
+        );
+        assert_eq!(
+            Scripts::parse("[s]\nraise_stat luck 1\n").err(),
+            Some("line 2: unknown stat 'luck'".to_string())
+        );
+    }
+
+    #[test]
+    fn bundled_item_scripts_exist() {
+        let scripts = Scripts::load_from_file(SCRIPTS_FILE).unwrap();
+        for item in [Item::key(), Item::lockpick()].iter() {
+            let name = format!("{}{}", ITEM_SCRIPT_PREFIX, item.kind.name());
+            assert!(scripts.get(&name).is_some(), "no script {}", name);
+        }
+    }
+
+    #[test]
+    fn opened_secret_passage_is_walked_through() {
+        let scripts = Scripts::load_from_file(SCRIPTS_FILE).unwrap();
+        let mut map = Map::load_from_file("data/Maps/Test.map").unwrap();
+        let passage = Vec2::new(12, 4);
+        let mobility = Mobility::default();
+        assert!(!map.can_move_with(passage, mobility));
+
+        let mut recorder = Recorder::default();
+        for name in map.use_scripts(passage, mobility) {
+            scripts.get(&name).unwrap().run(&mut recorder);
+        }
+        for (position, key) in recorder.tiles.drain(..) {
+            let (x, y) = (position.x as usize, position.y as usize);
+            map.set_tile(x, y, MapTile::from_key(&key));
+        }
+
+        // Passage is entered now, bumping into it doesn't run the script again.
+        assert!(map.can_move_with(passage, mobility));
+        assert!(map.use_scripts(passage, mobility).is_empty());
+    }
+}
@@ -0,0 +1,307 @@
+use crate::{map::registry, tiles::BorderSet};
+use console_engine::{
+    pixel::{self, Pixel},
+    Color,
+};
+use std::{
+    collections::HashMap,
+    fs,
+    io::{self, ErrorKind},
+};
+
+pub const THEMES_DIR: &str = "data/Themes";
+pub const DEFAULT_THEME: &str = "default";
+
+const BORDER_SECTION: &str = "[border]";
+const TILES_SECTION: &str = "[tiles]";
+const CONNECTED_SECTION: &str = "[connected]";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TileStyle {
+    pub glyph: char,
+    pub fg: Color,
+    pub bg: Color,
+}
+
+impl TileStyle {
+    pub fn new(glyph: char, fg: Color, bg: Color) -> Self {
+        Self { glyph, fg, bg }
+    }
+
+    pub fn pixel(&self) -> Pixel {
+        pixel::pxl_fbg(self.glyph, self.fg, self.bg)
+    }
+
+    /// Pixel with style colors, but with different glyph. Used by multishape tiles.
+    pub fn pixel_with(&self, glyph: char) -> Pixel {
+        pixel::pxl_fbg(glyph, self.fg, self.bg)
+    }
+}
+
+/// Glyph set of tile which joins with its neighbours, like walls or roads do.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Connection {
+    pub glyphs: BorderSet,
+    /// Style keys of tiles this tile connects to.
+    pub connects_to: Vec<String>,
+}
+
+impl Connection {
+    pub fn new(glyphs: BorderSet, connects_to: &[&str]) -> Self {
+        Self {
+            glyphs,
+            connects_to: connects_to.iter().map(|key| key.to_string()).collect(),
+        }
+    }
+
+    pub fn connects_to(&self, key: &str) -> bool {
+        self.connects_to.iter().any(|connected| connected == key)
+    }
+}
+
+/// Window frame glyphs and tile looks, which replace ones defined in tile registry.
+pub struct Theme {
+    border: BorderSet,
+    styles: HashMap<String, TileStyle>,
+    connected: HashMap<String, Connection>,
+}
+
+impl Default for Theme {
+    /// Theme which draws everything as defined in tile registry.
+    fn default() -> Self {
+        Self {
+            border: BorderSet::HEAVY,
+            styles: HashMap::new(),
+            connected: HashMap::new(),
+        }
+    }
+}
+
+impl Theme {
+    /// Loads theme with given name from themes directory.
+    pub fn load(name: &str) -> io::Result<Self> {
+        Self::load_from_file(&format!("{}/{}.theme", THEMES_DIR, name))
+    }
+
+    pub fn load_from_file(file_name: &str) -> io::Result<Self> {
+        let raw_data = fs::read_to_string(file_name)?;
+        Self::parse(&raw_data).map_err(|error| io::Error::new(ErrorKind::InvalidData, error))
+    }
+
+    pub fn border(&self) -> &BorderSet {
+        &self.border
+    }
+
+    /// Style of the tile with given key. Tiles not styled by theme use registry style.
+    pub fn style(&self, key: &str) -> TileStyle {
+        match self.styles.get(key) {
+            Some(style) => *style,
+            None => registry::get().tile(key).style,
+        }
+    }
+
+    /// Returns glyph set for tiles which join with neighbours or `None` for single glyph tiles.
+    pub fn connection(&self, key: &str) -> Option<&Connection> {
+        self.connected
+            .get(key)
+            .or_else(|| registry::get().tile(key).connection.as_ref())
+    }
+
+    fn parse(raw_data: &str) -> Result<Self, String> {
+        let mut border = None;
+        let mut styles = HashMap::new();
+        let mut connected = HashMap::new();
+        let mut section = "";
+
+        for (idx, line) in raw_data.lines().enumerate() {
+            let line_number = idx + 1;
+            if line.trim().is_empty() || line.trim_start().starts_with(';') {
+                continue;
+            }
+
+            if [BORDER_SECTION, TILES_SECTION, CONNECTED_SECTION].contains(&line.trim()) {
+                section = line.trim();
+                continue;
+            }
+
+            match section {
+                BORDER_SECTION => {
+                    border = Some(BorderSet::from_glyphs(line.trim()).ok_or(format!(
+                        "line {}: border needs exactly 11 glyphs",
+                        line_number
+                    ))?);
+                }
+                TILES_SECTION => {
+                    let (key, style) = Self::parse_style(line)
+                        .map_err(|error| format!("line {}: {}", line_number, error))?;
+                    styles.insert(key, style);
+                }
+                CONNECTED_SECTION => {
+                    let (key, connection) = Self::parse_connection(line)
+                        .map_err(|error| format!("line {}: {}", line_number, error))?;
+                    connected.insert(key, connection);
+                }
+                _ => return Err(format!("line {}: text outside of section", line_number)),
+            }
+        }
+
+        Ok(Self {
+            border: border.ok_or(format!("{} section is missing", BORDER_SECTION))?,
+            styles,
+            connected,
+        })
+    }
+
+    /// Parses connected tile line in format: `key = glyphs connected_key connected_key ...`.
+    fn parse_connection(line: &str) -> Result<(String, Connection), String> {
+        let (key, value) = Self::split_tile_line(line)?;
+        let connection = parse_connection_value(value)?;
+
+        let registry = registry::get();
+        if let Some(unknown) = connection
+            .connects_to
+            .iter()
+            .find(|key| !registry.contains(key))
+        {
+            return Err(format!("unknown tile '{}'", unknown));
+        }
+
+        Ok((key, connection))
+    }
+
+    /// Parses style line in format: `key = 'glyph' foreground background`.
+    fn parse_style(line: &str) -> Result<(String, TileStyle), String> {
+        let (key, value) = Self::split_tile_line(line)?;
+        Ok((key, parse_style_value(value)?))
+    }
+
+    fn split_tile_line(line: &str) -> Result<(String, &str), String> {
+        let mut parts = line.splitn(2, '=');
+        let key = parts.next().unwrap_or("").trim();
+        let value = parts.next().ok_or("missing '='")?.trim();
+
+        if !registry::get().contains(key) {
+            return Err(format!("unknown tile '{}'", key));
+        }
+
+        Ok((key.to_string(), value))
+    }
+}
+
+/// Parses glyph written in single quotes. Returns glyph and text which follows it.
+pub fn parse_quoted_glyph(value: &str) -> Result<(char, &str), String> {
+    let mut chars = value.chars();
+    match (chars.next(), chars.next(), chars.next()) {
+        (Some('\''), Some(glyph), Some('\'')) => Ok((glyph, chars.as_str())),
+        _ => Err("glyph has to be written in single quotes".to_string()),
+    }
+}
+
+/// Parses style in format: `'glyph' foreground background`.
+pub fn parse_style_value(value: &str) -> Result<TileStyle, String> {
+    let (glyph, rest) = parse_quoted_glyph(value)?;
+
+    let colors: Vec<&str> = rest.split_whitespace().collect();
+    if colors.len() != 2 {
+        return Err("expected foreground and background color".to_string());
+    }
+
+    Ok(TileStyle::new(
+        glyph,
+        parse_color(colors[0])?,
+        parse_color(colors[1])?,
+    ))
+}
+
+/// Parses connected glyphs in format: `glyphs connected_key connected_key ...`.
+/// Connected keys are checked by the caller.
+pub fn parse_connection_value(value: &str) -> Result<Connection, String> {
+    let mut values = value.split_whitespace();
+    let glyphs = values
+        .next()
+        .and_then(BorderSet::from_glyphs)
+        .ok_or("connected tile needs exactly 11 glyphs")?;
+
+    let connects_to: Vec<&str> = values.collect();
+    Ok(Connection::new(glyphs, &connects_to))
+}
+
+fn parse_color(name: &str) -> Result<Color, String> {
+    let color = match name {
+        "Reset" => Color::Reset,
+        "Black" => Color::Black,
+        "DarkGrey" => Color::DarkGrey,
+        "Red" => Color::Red,
+        "DarkRed" => Color::DarkRed,
+        "Green" => Color::Green,
+        "DarkGreen" => Color::DarkGreen,
+        "Yellow" => Color::Yellow,
+        "DarkYellow" => Color::DarkYellow,
+        "Blue" => Color::Blue,
+        "DarkBlue" => Color::DarkBlue,
+        "Magenta" => Color::Magenta,
+        "DarkMagenta" => Color::DarkMagenta,
+        "Cyan" => Color::Cyan,
+        "DarkCyan" => Color::DarkCyan,
+        "White" => Color::White,
+        "Grey" => Color::Grey,
+        _ => return Err(format!("unknown color '{}'", name)),
+    };
+
+    Ok(color)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::map::registry::key;
+
+    #[test]
+    fn bundled_themes_load() {
+        for name in ["default", "high_contrast", "ascii"].iter() {
+            assert!(Theme::load(name).is_ok(), "theme {} not loaded", name);
+        }
+
+        let ascii = Theme::load("ascii").unwrap();
+        assert_eq!(
+            *ascii.border(),
+            BorderSet::from_glyphs("-|+++++++++").unwrap()
+        );
+        // Tiles defined only in tiles file need their own ASCII glyphs too.
+        let data_tiles = ["barrel", "corpse", "merchant", "npc"];
+        for key in key::ALL.iter().chain(data_tiles.iter()) {
+            assert!(
+                ascii.styles.contains_key(*key),
+                "no ASCII style for {}",
+                key
+            );
+            assert!(ascii.style(key).glyph.is_ascii(), "{} isn't ASCII", key);
+        }
+        let mut connected_glyphs = key::ALL
+            .iter()
+            .filter_map(|key| ascii.connection(key))
+            .map(|connection| connection.glyphs.connecting_glyph(true, true, true, true));
+        assert!(connected_glyphs.all(|glyph| glyph.is_ascii()));
+
+        let glyph = |key| match ascii.connection(key) {
+            Some(connection) => connection.glyphs.connecting_glyph(true, true, true, true),
+            None => ascii.style(key).glyph,
+        };
+        assert_ne!(glyph(key::ROAD), glyph(key::WALL));
+    }
+
+    #[test]
+    fn parse_errors() {
+        let missing_quotes = "[border]\n-|+++++++++\n[tiles]\nempty = x Reset Reset\n";
+        assert_eq!(
+            Theme::parse(missing_quotes).err(),
+            Some("line 4: glyph has to be written in single quotes".to_string())
+        );
+
+        let bad_color = "[tiles]\ngrass = '.' Pink Reset\n";
+        assert_eq!(
+            Theme::parse(bad_color).err(),
+            Some("line 2: unknown color 'Pink'".to_string())
+        );
+    }
+}
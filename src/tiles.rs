@@ -12,16 +12,74 @@ pub mod border {
     pub const BOTTOM_RIGHT: char = '┛';
 }
 
-pub mod door {
-    pub const LOCKED: char = 'D';
-    pub const UNLOCKED: char = 'd';
+/// Glyphs used to draw frames and multishape tiles.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BorderSet {
+    pub horizontal: char,
+    pub vertical: char,
+    pub top_left: char,
+    pub top_center: char,
+    pub top_right: char,
+    pub center_left: char,
+    pub center_center: char,
+    pub center_right: char,
+    pub bottom_left: char,
+    pub bottom_center: char,
+    pub bottom_right: char,
 }
 
-pub mod window {
-    pub const LOCKED: char = 'W';
-    pub const UNLOCKED: char = 'w';
-}
+impl BorderSet {
+    pub const HEAVY: BorderSet = BorderSet {
+        horizontal: border::HORIZONTAL,
+        vertical: border::VERTICAL,
+        top_left: border::TOP_LEFT,
+        top_center: border::TOP_CENTER,
+        top_right: border::TOP_RIGHT,
+        center_left: border::CENTER_LEFT,
+        center_center: border::CENTER_CENTER,
+        center_right: border::CENTER_RIGHT,
+        bottom_left: border::BOTTOM_LEFT,
+        bottom_center: border::BOTTOM_CENTER,
+        bottom_right: border::BOTTOM_RIGHT,
+    };
+
+    /// Creates border set from 11 glyphs given in the same order as struct fields.
+    pub fn from_glyphs(glyphs: &str) -> Option<Self> {
+        let glyphs: Vec<char> = glyphs.chars().collect();
+        if glyphs.len() != 11 {
+            return None;
+        }
 
-pub const EMPTY: char = ' ';
-pub const GRASS: char = '.';
-pub const PLAYER: char = '☻';
+        Some(Self {
+            horizontal: glyphs[0],
+            vertical: glyphs[1],
+            top_left: glyphs[2],
+            top_center: glyphs[3],
+            top_right: glyphs[4],
+            center_left: glyphs[5],
+            center_center: glyphs[6],
+            center_right: glyphs[7],
+            bottom_left: glyphs[8],
+            bottom_center: glyphs[9],
+            bottom_right: glyphs[10],
+        })
+    }
+
+    /// Chooses glyph which joins tile with its connected neighbours.
+    pub fn connecting_glyph(&self, up: bool, down: bool, left: bool, right: bool) -> char {
+        match (up, down, left, right) {
+            (false, false, false, false) => self.center_center,
+            (_, _, false, false) => self.vertical,
+            (false, false, _, _) => self.horizontal,
+            (false, true, false, true) => self.top_left,
+            (false, true, true, false) => self.top_right,
+            (true, false, false, true) => self.bottom_left,
+            (true, false, true, false) => self.bottom_right,
+            (false, true, true, true) => self.top_center,
+            (true, false, true, true) => self.bottom_center,
+            (true, true, false, true) => self.center_left,
+            (true, true, true, false) => self.center_right,
+            (true, true, true, true) => self.center_center,
+        }
+    }
+}
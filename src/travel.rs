@@ -0,0 +1,165 @@
+use crate::{
+    event::Event,
+    map::{pathfinding, Map, Mobility},
+    vector::Vec2,
+};
+use std::collections::VecDeque;
+
+/// Position remembered by player, which can be used as travel destination.
+pub struct Landmark {
+    pub name: String,
+    pub position: Vec2,
+}
+
+impl Landmark {
+    pub fn new(name: String, position: Vec2) -> Self {
+        Self { name, position }
+    }
+}
+
+/// Movement done automatically, one step per frame, until destination is reached.
+pub enum Travel {
+    /// Walk to the nearest reachable tile which wasn't visited yet.
+    Explore,
+    /// Walk to chosen position along precomputed path.
+    To { target: Vec2, path: VecDeque<Vec2> },
+}
+
+impl Travel {
+    pub fn to(map: &Map, from: Vec2, target: Vec2, mobility: Mobility) -> Option<Self> {
+        let path = pathfinding::find_path(map, from, target, mobility)?;
+        if path.is_empty() {
+            return None;
+        }
+
+        Some(Travel::To {
+            target,
+            path: path.into_iter().collect(),
+        })
+    }
+
+    /// Returns direction of the next step or `None` if there is nowhere to go.
+    pub fn next_step(
+        &mut self,
+        map: &Map,
+        event: &Event,
+        position: Vec2,
+        mobility: Mobility,
+    ) -> Option<Vec2> {
+        let next = match self {
+            Travel::Explore => {
+                let (_, path) =
+                    pathfinding::nearest(map, position, mobility, |pos, _| !event.is_visited(pos))?;
+                *path.first()?
+            }
+            Travel::To { target, path } => {
+                let mut next = path.pop_front()?;
+                if !pathfinding::is_walkable(map, next, mobility) {
+                    // Map changed or trap was found since path was planned, so look for
+                    // another way.
+                    let new_path = pathfinding::find_path(map, position, *target, mobility)?;
+                    *path = new_path.into_iter().collect();
+                    next = path.pop_front()?;
+                }
+                next
+            }
+        };
+
+        Some(next - position)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::map::MapTile;
+
+    fn map(data: &str) -> Map {
+        Map::parse_data(data.to_string(), String::new())
+    }
+
+    /// Follows travel until it stops, returning visited positions.
+    fn walk(travel: &mut Travel, map: &Map, event: &mut Event, mut position: Vec2) -> Vec<Vec2> {
+        let mut positions = vec![];
+        while let Some(step) = travel.next_step(map, event, position, Mobility::default()) {
+            position += step;
+            event.player_moved(position);
+            positions.push(position);
+        }
+        positions
+    }
+
+    #[test]
+    fn travel_follows_path_to_target() {
+        let map = map("######\n#@   #\n######\n");
+        let start = map.player_position().unwrap();
+        let target = Vec2::new(4, 1);
+        let mut travel = Travel::to(&map, start, target, Mobility::default()).unwrap();
+
+        let positions = walk(&mut travel, &map, &mut Event::new(), start);
+        assert_eq!(
+            positions,
+            vec![Vec2::new(2, 1), Vec2::new(3, 1), Vec2::new(4, 1)]
+        );
+        assert!(Travel::to(&map, start, start, Mobility::default()).is_none());
+    }
+
+    #[test]
+    fn blocked_path_is_planned_again() {
+        let data = "#######\n#@    #\n# ### #\n#     #\n#######\n";
+        let mut map = map(data);
+        let start = map.player_position().unwrap();
+        let target = Vec2::new(5, 3);
+        let mut travel = Travel::to(&map, start, target, Mobility::default()).unwrap();
+
+        // Block the planned first step, so travel has to go around the other way.
+        let blocked = match &travel {
+            Travel::To { path, .. } => path[0],
+            Travel::Explore => unreachable!(),
+        };
+        map.set_tile(blocked.x as usize, blocked.y as usize, MapTile::Wall);
+
+        let positions = walk(&mut travel, &map, &mut Event::new(), start);
+        assert!(!positions.contains(&blocked));
+        assert_eq!(positions.last(), Some(&target));
+        assert_eq!(positions.len(), 6);
+
+        // Travel stops when there is no way left.
+        let mut travel = Travel::to(&map, start, target, Mobility::default()).unwrap();
+        map.set_tile(1, 2, MapTile::Wall);
+        map.set_tile(2, 1, MapTile::Wall);
+        assert_eq!(
+            travel.next_step(&map, &Event::new(), start, Mobility::default()),
+            None
+        );
+    }
+
+    #[test]
+    fn explore_goes_to_nearest_unvisited_tile() {
+        let map = map("#######\n#  @  #\n#######\n");
+        let start = map.player_position().unwrap();
+        let mut event = Event::new();
+        event.player_moved(Vec2::new(2, 1));
+        event.player_moved(start);
+
+        let mut travel = Travel::Explore;
+        assert_eq!(
+            travel.next_step(&map, &event, start, Mobility::default()),
+            Some(Vec2::RIGHT)
+        );
+
+        // Both ends are two steps away, the upper left one is chosen first.
+        event.player_moved(Vec2::new(4, 1));
+        assert_eq!(
+            travel.next_step(&map, &event, start, Mobility::default()),
+            Some(Vec2::LEFT)
+        );
+
+        let positions = walk(&mut travel, &map, &mut event, start);
+        assert_eq!(positions.last(), Some(&Vec2::new(5, 1)));
+        assert_eq!(
+            travel.next_step(&map, &event, start, Mobility::default()),
+            None
+        );
+    }
+}
@@ -1,12 +1,44 @@
+mod bestiary_window;
+mod character_window;
+mod console_window;
+mod enemy_panel;
 mod event_window;
+mod exploration_window;
+mod journal_window;
+mod log_window;
+mod map_window;
 mod simple_player_scr;
 mod text_scr;
+mod trade_window;
+mod transfer_window;
+pub mod widget;
 
-use crate::tiles;
+use crate::{tiles::BorderSet, vector::Vec2};
+pub use bestiary_window::BestiaryWindow;
+pub use character_window::CharacterWindow;
 use console_engine::{pixel, screen::Screen};
-pub use event_window::EventWindow;
+pub use console_window::ConsoleWindow;
+pub use enemy_panel::enemy_panel;
+pub use event_window::{EventWindow, Response};
+pub use exploration_window::{heat_color, ExplorationWindow};
+pub use journal_window::JournalWindow;
+pub use log_window::{log_panel, LogWindow};
+pub use map_window::{explored_tiles, minimap, MapWindow, Marker};
 pub use simple_player_scr::SimplePlayerScr;
 pub use text_scr::TextScr;
+pub use trade_window::{TradeSide, TradeWindow};
+pub use transfer_window::TransferWindow;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+use widget::Rect;
+
+/// Columns taken by side panel, together with line separating it from the map.
+const SIDE_PANEL_WIDTH: u32 = 20;
+/// Rows of player stats at the top of side panel.
+const STATS_HEIGHT: u32 = 10;
+/// Rows of message log at the bottom of side panel.
+const LOG_HEIGHT: u32 = 4;
+/// Narrower terminals show side panel over the map, only when it is toggled on.
+const DOCKED_PANEL_MIN_WIDTH: u32 = 80;
 
 pub struct Size {
     pub width: u32,
@@ -19,21 +51,195 @@ impl Size {
     }
 }
 
-pub fn main_layout_screen(width: u32, height: u32) -> Screen {
-    let mut s = Screen::new(width, height);
+/// Draws frame around the whole screen.
+pub fn draw_frame(screen: &mut Screen, border: &BorderSet) {
+    widget::frame(screen, Rect::of_screen(screen), border, "");
+}
+
+/// Splits text into lines not wider than `width` terminal columns, breaking at spaces where
+/// possible. New line characters in text start new lines.
+pub fn wrap_text(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            let mut word = word.to_string();
+            // Words wider than line are cut.
+            while word.width() > width {
+                if !line.is_empty() {
+                    lines.push(std::mem::take(&mut line));
+                }
+                let rest = word.split_off(split_index(&word, width));
+                lines.push(word);
+                word = rest;
+            }
+
+            if !line.is_empty() && line.width() + 1 + word.width() > width {
+                lines.push(std::mem::take(&mut line));
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(&word);
+        }
+        lines.push(line);
+    }
+
+    lines
+}
 
-    s.v_line(60, 0, height as i32, pixel::pxl(tiles::border::VERTICAL));
-    s.h_line(61, 10, width as i32, pixel::pxl(tiles::border::HORIZONTAL));
-    s.h_line(
-        61,
-        height as i32 - 5,
-        width as i32,
-        pixel::pxl(tiles::border::HORIZONTAL),
-    );
+/// Byte index at which text is cut to fit in `width` columns. At least one character is
+/// kept, so wide characters don't stop wrapping.
+fn split_index(text: &str, width: usize) -> usize {
+    let mut used = 0;
+    for (index, ch) in text.char_indices() {
+        used += ch.width().unwrap_or(0);
+        if used > width && index > 0 {
+            return index;
+        }
+    }
+    text.len()
+}
 
-    let pxl = pixel::pxl(tiles::border::CENTER_LEFT);
-    s.set_pxl(60, 10, pxl);
-    s.set_pxl(60, height as i32 - 5, pxl);
+/// Areas of the main screen: map on the left and side panel with player stats, information
+/// and message log on the right. On narrow terminals map takes the whole screen and panel
+/// covers its right part.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MainLayout {
+    pub map: Rect,
+    pub panel: Rect,
+    pub stats: Rect,
+    pub info: Rect,
+    pub log: Rect,
+    /// Panel is next to the map instead of over it.
+    pub docked: bool,
+}
+
+impl MainLayout {
+    pub fn new(width: u32, height: u32) -> Self {
+        let screen = Rect::new(0, 0, width, height);
+        let docked = width >= DOCKED_PANEL_MIN_WIDTH;
+        let (map, panel) = screen.split_left(width.saturating_sub(SIDE_PANEL_WIDTH));
+        // Panel parts are separated by lines.
+        let (_, inner) = panel.split_left(1);
+        let (stats, rest) = inner.split_top(STATS_HEIGHT);
+        let (_, rest) = rest.split_top(1);
+        let (info, log) = rest.split_bottom(LOG_HEIGHT);
+        let (info, _) = info.split_bottom(1);
+
+        Self {
+            map: if docked { map } else { screen },
+            panel,
+            stats,
+            info,
+            log,
+            docked,
+        }
+    }
+}
+
+/// Map position shown in the top left corner of `view`. Map larger than the view is scrolled
+/// to keep `focus` in the middle, but not past map edges.
+pub fn camera(view: Rect, map_size: Size, focus: Vec2) -> Vec2 {
+    let axis = |focus: i32, view: u32, map: u32| {
+        if map <= view {
+            0
+        } else {
+            (focus - view as i32 / 2).clamp(0, (map - view) as i32)
+        }
+    };
+
+    Vec2::new(
+        axis(focus.x, view.width, map_size.width),
+        axis(focus.y, view.height, map_size.height),
+    )
+}
+
+/// Draws empty side panel with lines separating it from the map and its parts from each
+/// other.
+pub fn side_panel_screen(layout: &MainLayout, border: &BorderSet) -> Screen {
+    let panel = layout.panel;
+    let mut s = Screen::new(panel.width, panel.height);
+
+    s.v_line(0, 0, panel.height as i32, pixel::pxl(border.vertical));
+    for y in [layout.stats.bottom(), layout.log.y - 1] {
+        let y = y - panel.y;
+        s.h_line(1, y, panel.width as i32, pixel::pxl(border.horizontal));
+        s.set_pxl(0, y, pixel::pxl(border.center_left));
+    }
 
     s
 }
+
+/// Message shown instead of the game when terminal is smaller than `min_width` x
+/// `min_height`.
+pub fn too_small_screen(width: u32, height: u32, min_width: u32, min_height: u32) -> Screen {
+    let mut screen = Screen::new(width, height);
+    let lines = [
+        "Terminal too small".to_string(),
+        format!(
+            "Need {}x{}, have {}x{}",
+            min_width, min_height, width, height
+        ),
+        "Resize it or press q to quit".to_string(),
+    ];
+
+    let area = Rect::of_screen(&screen);
+    let top = (height as i32 - lines.len() as i32) / 2;
+    for (idx, line) in lines.iter().enumerate() {
+        let row = Rect::new(0, top + idx as i32, area.width, 1);
+        widget::label(
+            &mut screen,
+            row,
+            line,
+            console_engine::Color::White,
+            widget::Align::Center,
+        );
+    }
+
+    screen
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text_is_wrapped_at_spaces() {
+        assert_eq!(
+            wrap_text("Few come down here\nalive. abcdefghij", 8),
+            vec!["Few come", "down", "here", "alive.", "abcdefgh", "ij"]
+        );
+        // Wide characters take two columns.
+        assert_eq!(wrap_text("日本語 語", 4), vec!["日本", "語", "語"]);
+    }
+
+    #[test]
+    fn panel_is_docked_on_wide_terminals() {
+        let wide = MainLayout::new(100, 40);
+        assert!(wide.docked);
+        assert_eq!(wide.map, Rect::new(0, 0, 80, 40));
+        assert_eq!(wide.stats, Rect::new(81, 0, 19, 10));
+        assert_eq!(wide.log, Rect::new(81, 36, 19, 4));
+
+        let narrow = MainLayout::new(60, 24);
+        assert!(!narrow.docked);
+        assert_eq!(narrow.map, Rect::new(0, 0, 60, 24));
+        assert_eq!(narrow.panel, Rect::new(40, 0, 20, 24));
+        assert_eq!(narrow.info, Rect::new(41, 11, 19, 8));
+    }
+
+    #[test]
+    fn camera_follows_focus_within_map() {
+        let view = Rect::new(0, 0, 40, 20);
+        let camera = |width, height, x, y| camera(view, Size::new(width, height), Vec2::new(x, y));
+
+        // Map smaller than the view isn't scrolled.
+        assert_eq!(camera(30, 10, 25, 8), Vec2::ZERO);
+        assert_eq!(camera(100, 50, 50, 25), Vec2::new(30, 15));
+        // Camera stops at map edges.
+        assert_eq!(camera(100, 50, 3, 2), Vec2::ZERO);
+        assert_eq!(camera(100, 50, 99, 49), Vec2::new(60, 30));
+    }
+}
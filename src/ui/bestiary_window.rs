@@ -0,0 +1,69 @@
+use super::{
+    widget::{self, Align, Rect},
+    Size,
+};
+use crate::{bestiary::Bestiary, enemy, tiles::BorderSet};
+use console_engine::{screen::Screen, Color, ConsoleEngine, KeyCode};
+
+const HELP: &str = "Up/Down select  Esc close";
+
+/// Window listing enemy kinds player has met with numbers of encounters and kills.
+pub struct BestiaryWindow {
+    size: Size,
+    selected: usize,
+}
+
+impl BestiaryWindow {
+    pub fn new(size: Size) -> Self {
+        Self { size, selected: 0 }
+    }
+
+    /// Moves selection. Returns true when window should be closed.
+    pub fn handle_input(&mut self, engine: &ConsoleEngine, bestiary: &Bestiary) -> bool {
+        if engine.is_key_pressed(KeyCode::Esc) || engine.is_key_pressed(KeyCode::Char('b')) {
+            return true;
+        }
+        widget::move_selection(engine, &mut self.selected, bestiary.records().count());
+        false
+    }
+
+    pub fn render(&self, border: &BorderSet, bestiary: &Bestiary) -> Screen {
+        let mut screen = Screen::new(self.size.width, self.size.height);
+        let area = Rect::of_screen(&screen);
+        widget::frame(&mut screen, area, border, "Bestiary");
+
+        let (body, help) = area.shrink(2, 1).split_bottom(1);
+        widget::label(&mut screen, help, HELP, Color::White, Align::Center);
+
+        let records: Vec<_> = bestiary.records().collect();
+        if records.is_empty() {
+            let text = "No enemies met yet.";
+            widget::label(&mut screen, body, text, Color::Grey, Align::Left);
+            return screen;
+        }
+
+        // Description of selected enemy below the table.
+        let (list, details) = body.split_bottom(3);
+        let columns = [("Enemy", 20), ("Met", 6), ("Killed", 6)];
+        let rows: Vec<Vec<String>> = records
+            .iter()
+            .map(|(kind, record)| {
+                vec![
+                    kind.to_string(),
+                    record.encountered.to_string(),
+                    record.killed.to_string(),
+                ]
+            })
+            .collect();
+        widget::table(&mut screen, list, &columns, &rows, Some(self.selected));
+
+        let (kind, _) = records[self.selected];
+        let description = super::wrap_text(enemy::description(kind), details.width as usize);
+        for (line, row) in description.iter().zip(details.y + 1..details.bottom()) {
+            let row = Rect::new(details.x, row, details.width, 1);
+            widget::label(&mut screen, row, line, Color::Grey, Align::Left);
+        }
+
+        screen
+    }
+}
@@ -0,0 +1,132 @@
+use super::{
+    widget::{self, Align, Rect},
+    Size,
+};
+use crate::{
+    item::{Item, MODIFIER_NAMES},
+    player::{Attribute, Player, Slot},
+    tiles::BorderSet,
+};
+use console_engine::{screen::Screen, Color, ConsoleEngine, KeyCode};
+
+const HELP: &str = "Up/Down select slot  Esc close";
+/// Short slot names used as table headings.
+const SLOT_HEADINGS: [&str; 7] = ["Hd", "Ch", "Lg", "Ar", "Ft", "LH", "RH"];
+
+/// Full screen character sheet with breakdown of every attribute and equipped items.
+pub struct CharacterWindow {
+    size: Size,
+    /// Index of selected slot in `Slot::ALL`.
+    selected: usize,
+}
+
+impl CharacterWindow {
+    pub fn new(size: Size) -> Self {
+        Self { size, selected: 0 }
+    }
+
+    pub fn resize(&mut self, size: Size) {
+        self.size = size;
+    }
+
+    /// Moves slot selection. Returns true when window should be closed.
+    pub fn handle_input(&mut self, engine: &ConsoleEngine) -> bool {
+        if engine.is_key_pressed(KeyCode::Esc) || engine.is_key_pressed(KeyCode::Char('c')) {
+            return true;
+        }
+        widget::move_selection(engine, &mut self.selected, Slot::ALL.len());
+        false
+    }
+
+    pub fn render(&self, border: &BorderSet, player: &Player) -> Screen {
+        let mut screen = Screen::new(self.size.width, self.size.height);
+        let area = Rect::of_screen(&screen);
+        let title = format!("{}, level {}", player.name(), player.level());
+        widget::frame(&mut screen, area, border, &title);
+
+        let (body, help) = area.shrink(2, 1).split_bottom(1);
+        widget::label(&mut screen, help, HELP, Color::White, Align::Center);
+
+        // Attribute table: base value, level bonus, modifier of every slot and total.
+        let mut columns = vec![("Attribute", 13), ("Base", 5), ("Lvl", 4)];
+        columns.extend(SLOT_HEADINGS.iter().map(|heading| (*heading, 4)));
+        columns.push(("Total", 5));
+        let rows: Vec<Vec<String>> = Attribute::ALL
+            .iter()
+            .map(|attribute| {
+                let breakdown = player.breakdown(*attribute);
+                let mut row = vec![
+                    attribute.name().to_string(),
+                    breakdown.base.to_string(),
+                    format!("+{}", breakdown.level),
+                ];
+                row.extend(breakdown.items.iter().map(|(_, value)| match value {
+                    0 => "-".to_string(),
+                    value => format!("{:+}", value),
+                }));
+                row.push(breakdown.total.to_string());
+                row
+            })
+            .collect();
+        let (table, rest) = body.split_top(rows.len() as u32 + 1);
+        widget::table(&mut screen, table, &columns, &rows, None);
+
+        // Equipped slots on the left, details of selected item on the right.
+        let (_, rest) = rest.split_top(1);
+        let (slots, details) = rest.split_left(rest.width / 2);
+        let slot_lines: Vec<String> = Slot::ALL
+            .iter()
+            .map(|slot| {
+                let item = player
+                    .equipped(*slot)
+                    .map_or("(empty)".to_string(), Item::to_string);
+                format!("{:<11}{}", slot.name(), item)
+            })
+            .collect();
+        widget::label(&mut screen, slots, "Equipment", Color::Yellow, Align::Left);
+        widget::list(
+            &mut screen,
+            slots.split_top(1).1,
+            &slot_lines,
+            Some(self.selected),
+        );
+
+        let slot = Slot::ALL[self.selected];
+        let (heading, details) = details.split_top(1);
+        widget::label(
+            &mut screen,
+            heading,
+            slot.name(),
+            Color::Yellow,
+            Align::Left,
+        );
+        for (row, line) in item_details(player.equipped(slot)).iter().enumerate() {
+            let row = Rect::new(details.x, details.y + row as i32, details.width, 1);
+            if row.y < details.bottom() {
+                widget::label(&mut screen, row, line, Color::White, Align::Left);
+            }
+        }
+
+        screen
+    }
+}
+
+/// Name, rarity, value and modifiers of item.
+fn item_details(item: Option<&Item>) -> Vec<String> {
+    let item = match item {
+        Some(item) => item,
+        None => return vec!["Nothing equipped.".to_string()],
+    };
+
+    let mut lines = vec![
+        item.to_string(),
+        format!("Rarity: {:?}", item.rarity()),
+        format!("Value: {} gold", item.value()),
+    ];
+    for (name, value) in MODIFIER_NAMES.iter().zip(item.modifiers().iter()) {
+        if *value != 0 {
+            lines.push(format!("{}: {:+}", name, value));
+        }
+    }
+    lines
+}
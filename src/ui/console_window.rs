@@ -0,0 +1,61 @@
+use super::{
+    widget::{self, Rect, TextInput},
+    Size,
+};
+use crate::tiles::BorderSet;
+use console_engine::{screen::Screen, ConsoleEngine};
+
+/// Debug console with command line and output of previous commands.
+pub struct ConsoleWindow {
+    size: Size,
+    input: TextInput,
+    output: Vec<String>,
+}
+
+impl ConsoleWindow {
+    pub fn new(size: Size) -> Self {
+        Self {
+            size,
+            input: TextInput::new(),
+            output: vec!["Type 'help' for list of commands".to_string()],
+        }
+    }
+
+    pub fn resize(&mut self, size: Size) {
+        self.size = size;
+    }
+
+    /// Edits command line. Returns command when Enter is pressed.
+    pub fn handle_input(&mut self, engine: &ConsoleEngine) -> Option<String> {
+        if !self.input.handle_input(engine) {
+            return None;
+        }
+
+        let command = self.input.text().to_string();
+        self.input.clear();
+        self.output.push(format!("> {}", command));
+        Some(command)
+    }
+
+    pub fn add_output(&mut self, lines: Vec<String>) {
+        self.output.extend(lines);
+    }
+
+    pub fn render(&self, border: &BorderSet) -> Screen {
+        let mut screen = Screen::new(self.size.width, self.size.height);
+        let area = Rect::of_screen(&screen);
+        widget::frame(&mut screen, area, border, "Console");
+
+        // Newest output is shown just above command line.
+        let rows = self.size.height as usize - 3;
+        let first = self.output.len().saturating_sub(rows);
+        for (y, line) in self.output[first..].iter().enumerate() {
+            screen.print(2, y as i32 + 1, line);
+        }
+
+        let (_, input) = area.shrink(2, 1).split_bottom(1);
+        self.input.render(&mut screen, input, "> ", true);
+
+        screen
+    }
+}
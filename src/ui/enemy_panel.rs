@@ -0,0 +1,89 @@
+use super::{
+    simple_player_scr::health_color,
+    widget::{self, Align, Rect},
+};
+use crate::{
+    bestiary::Record,
+    enemy::{self, Enemy, Estimate, Threat},
+    tiles::BorderSet,
+};
+use console_engine::{screen::Screen, Color};
+
+const WIDTH: u32 = 36;
+const BAR_WIDTH: u32 = 16;
+
+pub fn threat_color(threat: Threat) -> Color {
+    match threat {
+        Threat::Harmless => Color::Grey,
+        Threat::Easy => Color::Green,
+        Threat::Fair => Color::Yellow,
+        Threat::Dangerous => Color::Red,
+        Threat::Deadly => Color::Magenta,
+    }
+}
+
+/// Window describing met enemy and expected course of fight with it.
+pub fn enemy_panel(
+    border: &BorderSet,
+    enemy: &Enemy,
+    estimate: &Estimate,
+    record: Record,
+) -> Screen {
+    let description = super::wrap_text(enemy::description(enemy.kind()), WIDTH as usize - 4);
+    // Frame, description, empty line and five rows of stats.
+    let height = description.len() as u32 + 8;
+    let mut screen = Screen::new(WIDTH, height);
+    let area = Rect::of_screen(&screen);
+    widget::frame(&mut screen, area, border, enemy.kind());
+
+    let mut rows = area.shrink(2, 1);
+    let mut next_row = || {
+        let (row, rest) = rows.split_top(1);
+        rows = rest;
+        row
+    };
+
+    for line in description.iter() {
+        widget::label(&mut screen, next_row(), line, Color::Grey, Align::Left);
+    }
+    next_row();
+
+    let (label, rest) = next_row().split_left(3);
+    let (bar, value) = rest.split_left(BAR_WIDTH + 1);
+    let color = health_color(enemy.health(), enemy.max_health());
+    widget::label(&mut screen, label, "HP", Color::White, Align::Left);
+    widget::progress_bar(
+        &mut screen,
+        bar,
+        enemy.health() as u32,
+        enemy.max_health() as u32,
+        color,
+    );
+    let health = format!("{}/{}", enemy.health(), enemy.max_health());
+    widget::label(&mut screen, value, &health, Color::White, Align::Left);
+
+    let stats = format!("Attack {}  Defense {}", enemy.attack(), enemy.defense());
+    widget::label(&mut screen, next_row(), &stats, Color::White, Align::Left);
+
+    let dealt = match estimate.rounds {
+        Some(rounds) => format!("You deal {} a hit, {} hits to win", estimate.dealt, rounds),
+        None => "You can't hurt it".to_string(),
+    };
+    widget::label(&mut screen, next_row(), &dealt, Color::White, Align::Left);
+    let taken = format!("It deals {} a hit", estimate.taken);
+    widget::label(&mut screen, next_row(), &taken, Color::White, Align::Left);
+
+    let row = next_row();
+    let threat = format!("Threat: {:?}", estimate.threat);
+    widget::label(
+        &mut screen,
+        row,
+        &threat,
+        threat_color(estimate.threat),
+        Align::Left,
+    );
+    let killed = format!("Killed {}", record.killed);
+    widget::label(&mut screen, row, &killed, Color::Grey, Align::Right);
+
+    screen
+}
@@ -1,100 +1,383 @@
 use super::Size;
-use crate::tiles;
-use console_engine::{pixel, screen::Screen, Color, ConsoleEngine, KeyCode};
+use crate::tiles::BorderSet;
+use console_engine::{screen::Screen, Color, ConsoleEngine, KeyCode};
+use unicode_width::UnicodeWidthStr;
 
 const OPTIONS_SPACING: usize = 3;
+const MIN_WIDTH: usize = 20;
+/// Longer contents are wrapped, also when they don't fit in width given to the window.
+const MAX_WIDTH: usize = 56;
+/// Rows of contents shown at once, longer contents are scrolled.
+const MAX_ROWS: usize = 12;
+/// Rows of options listed one below another shown at once, they are scrolled to keep
+/// selected option visible.
+const MAX_OPTION_ROWS: usize = 8;
 
+/// What player did with the window.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Response {
+    /// Option with given index was confirmed with Enter or its hotkey.
+    Chosen(usize),
+    /// Window was closed with Esc.
+    Cancelled,
+}
+
+/// Window with text and options to choose from. Options can have hotkeys written in
+/// brackets, like `[F]ight`.
 pub struct EventWindow {
     title: String,
-    contents: String,
+    lines: Vec<String>,
     size: Size,
     options: Vec<String>,
+    /// Lines of every option. Options listed one below another are wrapped to window width.
+    option_lines: Vec<Vec<String>>,
     selected: usize,
+    /// Options are listed one below another, because they don't fit in one row.
+    vertical: bool,
+    /// Index of the first visible line of contents.
+    scroll: usize,
+    /// Index of the first visible line of options listed one below another.
+    option_scroll: usize,
 }
 
 impl EventWindow {
-    pub fn new(size: Size, contents: String, title: String, options: Vec<String>) -> Self {
+    /// Creates window sized to fit its contents, title and options, but not wider than
+    /// `max_width`.
+    pub fn new(contents: String, title: String, options: Vec<String>, max_width: u32) -> Self {
+        assert!(!options.is_empty());
+
+        let max_width = (max_width as usize).clamp(MIN_WIDTH, MAX_WIDTH);
+        let row_width = options.iter().map(|option| option.width()).sum::<usize>()
+            + (options.len() - 1) * OPTIONS_SPACING;
+        let contents_width = contents.lines().map(|line| line.width()).max();
+        let inner_width = [contents_width.unwrap_or(0), row_width, title.width() + 2]
+            .iter()
+            .copied()
+            .max()
+            .unwrap_or(0)
+            .clamp(MIN_WIDTH - 4, max_width - 4);
+
+        let lines = super::wrap_text(&contents, inner_width);
+        let vertical = row_width > inner_width;
+        let option_lines: Vec<Vec<String>> = options
+            .iter()
+            .map(|option| {
+                if vertical {
+                    super::wrap_text(option, inner_width)
+                } else {
+                    vec![option.clone()]
+                }
+            })
+            .collect();
+        let option_rows = if vertical {
+            option_lines
+                .iter()
+                .map(|lines| lines.len())
+                .sum::<usize>()
+                .min(MAX_OPTION_ROWS)
+        } else {
+            1
+        };
+        // Frame, contents, empty line and options.
+        let height = lines.len().min(MAX_ROWS) + option_rows + 3;
+
         Self {
             title,
-            contents,
-            size,
+            lines,
+            size: Size::new(inner_width as u32 + 4, height as u32),
             options,
+            option_lines,
             selected: 0,
+            vertical,
+            scroll: 0,
+            option_scroll: 0,
         }
     }
 
-    pub fn handle_input(&mut self, engine: &ConsoleEngine) {
-        if engine.is_key_pressed(KeyCode::Left) {
+    /// Moves selection and scrolls contents. Returns response when option is chosen or
+    /// window is cancelled.
+    pub fn handle_input(&mut self, engine: &ConsoleEngine) -> Option<Response> {
+        if engine.is_key_pressed(KeyCode::Esc) {
+            return Some(Response::Cancelled);
+        }
+        if engine.is_key_pressed(KeyCode::Enter) {
+            return Some(Response::Chosen(self.selected));
+        }
+        for (idx, option) in self.options.iter().enumerate() {
+            if let Some((_, key)) = hotkey(option) {
+                if engine.is_key_pressed(KeyCode::Char(key)) {
+                    self.select(idx);
+                    return Some(Response::Chosen(idx));
+                }
+            }
+        }
+
+        let max_scroll = self.lines.len() - self.rows();
+        if engine.is_key_pressed(KeyCode::PageUp) {
+            self.scroll = self.scroll.saturating_sub(self.rows());
+        } else if engine.is_key_pressed(KeyCode::PageDown) {
+            self.scroll = (self.scroll + self.rows()).min(max_scroll);
+        }
+
+        let (previous, next) = if self.vertical {
+            (KeyCode::Up, KeyCode::Down)
+        } else {
+            // Up and down aren't needed by options in one row, so they scroll.
+            if engine.is_key_pressed(KeyCode::Up) {
+                self.scroll = self.scroll.saturating_sub(1);
+            } else if engine.is_key_pressed(KeyCode::Down) {
+                self.scroll = (self.scroll + 1).min(max_scroll);
+            }
+            (KeyCode::Left, KeyCode::Right)
+        };
+
+        if engine.is_key_pressed(previous) {
             if self.selected == 0 {
-                self.selected = self.options.len() - 1;
+                self.select(self.options.len() - 1);
             } else {
-                self.selected -= 1;
+                self.select(self.selected - 1);
             }
-        } else if engine.is_key_pressed(KeyCode::Right) {
+        } else if engine.is_key_pressed(next) {
             if self.selected == self.options.len() - 1 {
-                self.selected = 0;
+                self.select(0);
             } else {
-                self.selected += 1;
+                self.select(self.selected + 1);
             }
         }
+
+        None
     }
 
-    pub fn render(&self) -> Screen {
-        assert!(self.options.len() > 0);
+    /// Selects option and scrolls options, so all its lines are visible. Beginning of option
+    /// longer than visible rows is shown.
+    fn select(&mut self, idx: usize) {
+        self.selected = idx;
+        if !self.vertical {
+            return;
+        }
 
-        let mut screen = Screen::new(self.size.width, self.size.height);
-        let width = self.size.width as i32 - 1;
-        let height = self.size.height as i32 - 1;
+        let first: usize = self.option_lines[..idx]
+            .iter()
+            .map(|lines| lines.len())
+            .sum();
+        let last = first + self.option_lines[idx].len();
+        let rows = self.option_rows();
+        if first < self.option_scroll {
+            self.option_scroll = first;
+        } else if last > self.option_scroll + rows {
+            self.option_scroll = (last - rows).min(first);
+        }
+    }
 
-        screen.h_line(0, 0, width, pixel::pxl(tiles::border::HORIZONTAL));
-        screen.h_line(0, height, width, pixel::pxl(tiles::border::HORIZONTAL));
-        screen.v_line(0, 0, height, pixel::pxl(tiles::border::VERTICAL));
-        screen.v_line(width, 0, height, pixel::pxl(tiles::border::VERTICAL));
-        screen.set_pxl(0, 0, pixel::pxl(tiles::border::TOP_LEFT));
-        screen.set_pxl(width, 0, pixel::pxl(tiles::border::TOP_RIGHT));
-        screen.set_pxl(0, height, pixel::pxl(tiles::border::BOTTOM_LEFT));
-        screen.set_pxl(width, height, pixel::pxl(tiles::border::BOTTOM_RIGHT));
+    pub fn render(&self, border: &BorderSet) -> Screen {
+        let mut screen = Screen::new(self.size.width, self.size.height);
+        super::draw_frame(&mut screen, border);
 
-        let center_x = |length| ((self.size.width - length as u32) / 2) as i32;
+        let width = self.size.width as i32;
+        let center_x = |length: usize| ((width - length as i32) / 2).max(0);
 
-        if self.title.len() > 0 {
+        if !self.title.is_empty() {
             // Put title at top frame in the center.
-            screen.print(center_x(self.title.len()), 0, &self.title);
+            screen.print(center_x(self.title.width()), 0, &self.title);
         }
 
-        if self.contents.len() > 0 {
-            // Put constents at the window center
-            screen.print(center_x(self.contents.len()), 1, &self.contents);
+        let rows = self.rows();
+        for (y, line) in self.lines.iter().skip(self.scroll).take(rows).enumerate() {
+            screen.print(2, y as i32 + 1, line);
         }
 
-        let mut spacings = self.options.len() - 1;
-        let options_len: usize = self.options.iter().map(|opt| opt.len()).sum();
-        let total_len = spacings * OPTIONS_SPACING + options_len;
-        let mut x = center_x(total_len);
+        // Arrows at right frame show that contents can be scrolled.
+        if self.scroll > 0 {
+            screen.print_fbg(width - 1, 1, "▲", Color::Yellow, Color::Reset);
+        }
+        if self.scroll + rows < self.lines.len() {
+            screen.print_fbg(width - 1, rows as i32, "▼", Color::Yellow, Color::Reset);
+        }
 
-        for (idx, option) in self.options.iter().enumerate() {
-            screen.print_fbg(
-                x,
-                2,
-                option,
-                Color::White,
-                if idx == self.selected {
-                    Color::DarkBlue
-                } else {
-                    Color::Black
-                },
-            );
+        // Options are separated from contents with empty line.
+        let options_y = rows as i32 + 2;
+        if self.vertical {
+            let option_rows = self.option_rows();
+            let visible = self
+                .option_lines
+                .iter()
+                .enumerate()
+                .flat_map(|(idx, lines)| lines.iter().map(move |line| (idx, line)))
+                .skip(self.option_scroll)
+                .take(option_rows);
+            for (y, (idx, line)) in visible.enumerate() {
+                self.print_option(&mut screen, idx, line, 2, options_y + y as i32);
+            }
 
-            if spacings > 0 {
-                x += option.len() as i32 + OPTIONS_SPACING as i32;
-                spacings -= 1;
+            let bottom = options_y + option_rows as i32 - 1;
+            if self.option_scroll > 0 {
+                screen.print_fbg(width - 1, options_y, "▲", Color::Yellow, Color::Reset);
+            }
+            if self.option_scroll + option_rows < self.option_line_count() {
+                screen.print_fbg(width - 1, bottom, "▼", Color::Yellow, Color::Reset);
+            }
+        } else {
+            let row_width = self
+                .options
+                .iter()
+                .map(|option| option.width())
+                .sum::<usize>()
+                + (self.options.len() - 1) * OPTIONS_SPACING;
+            let mut x = center_x(row_width);
+            for (idx, option) in self.options.iter().enumerate() {
+                self.print_option(&mut screen, idx, option, x, options_y);
+                x += (option.width() + OPTIONS_SPACING) as i32;
             }
         }
 
         screen
     }
 
-    pub fn selected(&self) -> usize {
-        self.selected
+    /// Rows of contents visible at once.
+    fn rows(&self) -> usize {
+        self.lines.len().min(MAX_ROWS)
+    }
+
+    /// Lines of all options listed one below another.
+    fn option_line_count(&self) -> usize {
+        self.option_lines.iter().map(|lines| lines.len()).sum()
+    }
+
+    /// Rows of options listed one below another visible at once.
+    fn option_rows(&self) -> usize {
+        self.option_line_count().min(MAX_OPTION_ROWS)
+    }
+
+    /// Prints line of option with highlighted hotkey, selected option has different
+    /// background.
+    fn print_option(&self, screen: &mut Screen, idx: usize, option: &str, x: i32, y: i32) {
+        let background = if idx == self.selected {
+            Color::DarkBlue
+        } else {
+            Color::Black
+        };
+
+        screen.print_fbg(x, y, option, Color::White, background);
+        if let Some((index, key)) = hotkey(option) {
+            let key_x = x + option[..index].width() as i32;
+            let key = option[index..].chars().next().unwrap_or(key);
+            screen.print_fbg(key_x, y, &key.to_string(), Color::Yellow, background);
+        }
+    }
+}
+
+/// Finds hotkey written in brackets, e.g. `[F]ight`. Returns byte index of the hotkey in
+/// option and lowercase key which chooses it.
+fn hotkey(option: &str) -> Option<(usize, char)> {
+    let start = option.find('[')? + 1;
+    let mut chars = option[start..].chars();
+    let key = chars.next()?;
+    if chars.next() == Some(']') && key.is_alphanumeric() {
+        Some((start, key.to_ascii_lowercase()))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn window_fits_contents() {
+        let short = EventWindow::new(
+            "A goblin.".to_string(),
+            "You meet enemy".to_string(),
+            vec!["[F]ight".to_string(), "[I]gnore".to_string()],
+            80,
+        );
+        assert_eq!((short.size.width, short.size.height), (22, 5));
+        assert!(!short.vertical);
+
+        let text = "word ".repeat(200);
+        let long = EventWindow::new(
+            text,
+            String::new(),
+            vec!["A very long answer to this question.".to_string(); 2],
+            80,
+        );
+        assert_eq!(long.size.width as usize, MAX_WIDTH);
+        assert!(long.vertical);
+        assert_eq!(long.size.height as usize, MAX_ROWS + 2 + 3);
+        long.render(&BorderSet::HEAVY);
+
+        assert_eq!(hotkey("[F]ight"), Some((1, 'f')));
+        assert_eq!(hotkey("Say [y]es"), Some((5, 'y')));
+        assert_eq!(hotkey("OK"), None);
+        assert_eq!(hotkey("[Fight]"), None);
+    }
+
+    #[test]
+    fn long_options_are_wrapped() {
+        let option = "(Intelligence) The key is useless to someone who never leaves the fire, \
+                      so give it to me."
+            .to_string();
+        assert!(option.width() > MAX_WIDTH);
+        let window = EventWindow::new(
+            "\"A key? I may have one.\"".to_string(),
+            "Hermit".to_string(),
+            vec![option, "[M]aybe later.".to_string()],
+            80,
+        );
+
+        assert!(window.vertical);
+        assert_eq!(window.size.width as usize, MAX_WIDTH);
+        let inner_width = MAX_WIDTH - 4;
+        assert!(window.option_lines[0].len() > 1);
+        assert!(window.option_lines[0]
+            .iter()
+            .all(|line| line.width() <= inner_width));
+        // Frame, one line of contents, empty line and every line of options.
+        let option_rows = window.option_lines[0].len() + 1;
+        assert_eq!(window.size.height as usize, 1 + option_rows + 3);
+        window.render(&BorderSet::HEAVY);
+    }
+
+    #[test]
+    fn many_options_are_scrolled() {
+        let options: Vec<String> = (0..6)
+            .map(|idx| format!("{}. {}", idx, "Long answer to the question. ".repeat(3)))
+            .collect();
+        let mut window =
+            EventWindow::new("Choose.".to_string(), String::new(), options.clone(), 80);
+        assert!(window.option_line_count() > MAX_OPTION_ROWS);
+        assert_eq!(window.size.height as usize, 1 + MAX_OPTION_ROWS + 3);
+
+        let last = options.len() - 1;
+        window.select(last);
+        // Last option is at the bottom of visible rows.
+        assert_eq!(
+            window.option_scroll + MAX_OPTION_ROWS,
+            window.option_line_count()
+        );
+        window.render(&BorderSet::HEAVY);
+
+        window.select(0);
+        assert_eq!(window.option_scroll, 0);
+    }
+
+    #[test]
+    fn window_fits_narrow_area() {
+        let window = EventWindow::new(
+            "word ".repeat(20),
+            String::new(),
+            vec!["OK".to_string()],
+            40,
+        );
+        assert_eq!(window.size.width, 40);
+        assert!(window.lines.iter().all(|line| line.width() <= 36));
+
+        let tiny = EventWindow::new(
+            "A goblin.".to_string(),
+            String::new(),
+            vec!["OK".to_string()],
+            10,
+        );
+        assert_eq!(tiny.size.width as usize, MIN_WIDTH);
     }
 }
@@ -0,0 +1,133 @@
+use super::{
+    widget::{self, Align, Rect},
+    Size,
+};
+use crate::{
+    exploration::{ExplorationStats, REGION_SIZE},
+    tiles::BorderSet,
+};
+use console_engine::{screen::Screen, Color, ConsoleEngine, KeyCode};
+
+const HELP: &str = "Up/Down select  Esc close";
+/// Lowest visit counts shown with heatmap colors.
+const HEAT_LEVELS: [(u8, Color); 5] = [
+    (1, Color::DarkBlue),
+    (2, Color::DarkGreen),
+    (4, Color::DarkYellow),
+    (8, Color::DarkRed),
+    (16, Color::DarkMagenta),
+];
+
+/// Background of tile visited `count` times in heatmap overlay.
+pub fn heat_color(count: u8) -> Color {
+    HEAT_LEVELS
+        .iter()
+        .rev()
+        .find(|(level, _)| count >= *level)
+        .map_or(Color::Reset, |(_, color)| *color)
+}
+
+/// Window with share of explored tiles and items and enemies found in every region of map.
+pub struct ExplorationWindow {
+    size: Size,
+    selected: usize,
+}
+
+impl ExplorationWindow {
+    pub fn new(size: Size) -> Self {
+        Self { size, selected: 0 }
+    }
+
+    /// Moves selection. Returns true when window should be closed.
+    pub fn handle_input(&mut self, engine: &ConsoleEngine, stats: &ExplorationStats) -> bool {
+        if engine.is_key_pressed(KeyCode::Esc) || engine.is_key_pressed(KeyCode::Char('e')) {
+            return true;
+        }
+        widget::move_selection(engine, &mut self.selected, stats.regions().len());
+        false
+    }
+
+    pub fn render(&self, border: &BorderSet, stats: &ExplorationStats) -> Screen {
+        let mut screen = Screen::new(self.size.width, self.size.height);
+        let area = Rect::of_screen(&screen);
+        widget::frame(&mut screen, area, border, "Exploration");
+
+        let (body, help) = area.shrink(2, 1).split_bottom(1);
+        widget::label(&mut screen, help, HELP, Color::White, Align::Center);
+
+        let total = stats.total();
+        let (row, body) = body.split_top(1);
+        let explored = format!(
+            "Explored {}/{} tiles ({}%)",
+            total.explored,
+            total.reachable,
+            total.percent_explored()
+        );
+        widget::label(&mut screen, row, &explored, Color::White, Align::Left);
+        let (row, body) = body.split_top(1);
+        let percent = total.percent_explored();
+        widget::progress_bar(&mut screen, row, percent, 100, Color::Green);
+        let (row, body) = body.split_top(1);
+        let found = format!("Items found {}  Enemies met {}", total.items, total.enemies);
+        widget::label(&mut screen, row, &found, Color::White, Align::Left);
+
+        // Legend of heatmap colors.
+        let (row, body) = body.split_top(2);
+        let (label, mut rest) = row.split_left(8);
+        widget::label(&mut screen, label, "Visits", Color::White, Align::Left);
+        for (idx, (level, color)) in HEAT_LEVELS.iter().enumerate() {
+            let text = match HEAT_LEVELS.get(idx + 1) {
+                Some((next, _)) if *next > level + 1 => format!("{}-{}", level, next - 1),
+                Some(_) => level.to_string(),
+                None => format!("{}+", level),
+            };
+            let (cell, next) = rest.split_left(text.len() as u32 + 1);
+            screen.print_fbg(cell.x, cell.y, &text, Color::White, *color);
+            rest = next;
+        }
+
+        let columns = [
+            ("Region", 12),
+            ("Explored", 10),
+            ("Items", 7),
+            ("Enemies", 8),
+        ];
+        let rows: Vec<Vec<String>> = stats
+            .regions()
+            .iter()
+            .map(|region| {
+                vec![
+                    format!("{},{}", region.origin.x, region.origin.y),
+                    format!("{}%", region.percent_explored()),
+                    region.items.to_string(),
+                    region.enemies.to_string(),
+                ]
+            })
+            .collect();
+        let (heading, table) = body.split_top(1);
+        let heading_text = format!("Regions of {0}x{0} tiles", REGION_SIZE);
+        widget::label(
+            &mut screen,
+            heading,
+            &heading_text,
+            Color::Yellow,
+            Align::Left,
+        );
+        widget::table(&mut screen, table, &columns, &rows, Some(self.selected));
+
+        screen
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn heat_grows_with_visits() {
+        assert_eq!(heat_color(0), Color::Reset);
+        assert_eq!(heat_color(1), Color::DarkBlue);
+        assert_eq!(heat_color(3), Color::DarkGreen);
+        assert_eq!(heat_color(255), Color::DarkMagenta);
+    }
+}
@@ -0,0 +1,55 @@
+use super::Size;
+use crate::tiles::BorderSet;
+use console_engine::{screen::Screen, Color};
+
+const HELP: &str = "Esc close";
+
+/// Window listing active quests with their objectives and titles of completed quests.
+pub struct JournalWindow {
+    size: Size,
+}
+
+impl JournalWindow {
+    pub fn new(size: Size) -> Self {
+        Self { size }
+    }
+
+    pub fn render(&self, border: &BorderSet, active: &[String], completed: &[String]) -> Screen {
+        let mut screen = Screen::new(self.size.width, self.size.height);
+        super::draw_frame(&mut screen, border);
+
+        let center_x = |length: usize| (self.size.width as i32 - length as i32) / 2;
+        screen.print(center_x("Journal".len()), 0, "Journal");
+
+        let width = self.size.width as usize - 4;
+        let none = ["(none)".to_string()];
+        let mut lines: Vec<(String, Color)> = Vec::new();
+        for (heading, entries) in [("Active", active), ("Completed", completed)] {
+            if !lines.is_empty() {
+                lines.push((String::new(), Color::White));
+            }
+            lines.push((heading.to_string(), Color::Yellow));
+
+            let entries = if entries.is_empty() {
+                &none[..]
+            } else {
+                entries
+            };
+            for entry in entries {
+                for line in super::wrap_text(entry, width) {
+                    lines.push((line, Color::White));
+                }
+            }
+        }
+
+        // Rows between frame and help line.
+        let rows = self.size.height as usize - 3;
+        for (y, (line, color)) in lines.iter().take(rows).enumerate() {
+            screen.print_fbg(2, y as i32 + 1, line, *color, Color::Reset);
+        }
+
+        screen.print(center_x(HELP.len()), self.size.height as i32 - 2, HELP);
+
+        screen
+    }
+}
@@ -0,0 +1,187 @@
+use super::{
+    widget::{self, Rect, TextInput},
+    Size,
+};
+use crate::{
+    message_log::{Category, MessageLog},
+    tiles::BorderSet,
+};
+use console_engine::{screen::Screen, Color, ConsoleEngine, KeyCode, KeyModifiers};
+
+const HELP: &str = "Up/Down scroll  / search  n/N older/newer  Esc close";
+
+fn category_color(category: Category) -> Color {
+    match category {
+        Category::Combat => Color::Red,
+        Category::Loot => Color::Yellow,
+        Category::System => Color::Grey,
+    }
+}
+
+/// Newest messages wrapped to panel width, the last one at the bottom.
+pub fn log_panel(size: Size, log: &MessageLog) -> Screen {
+    let mut screen = Screen::new(size.width, size.height);
+
+    let mut lines: Vec<(String, Color)> = Vec::new();
+    for message in log.messages().iter().rev() {
+        if lines.len() >= size.height as usize {
+            break;
+        }
+        let color = category_color(message.category);
+        let wrapped = super::wrap_text(&message.text, size.width as usize);
+        lines.splice(0..0, wrapped.into_iter().map(|line| (line, color)));
+    }
+
+    let first = lines.len().saturating_sub(size.height as usize);
+    for (y, (line, color)) in lines[first..].iter().enumerate() {
+        screen.print_fbg(0, y as i32, line, *color, Color::Reset);
+    }
+
+    screen
+}
+
+/// Full screen view of message log, which can be scrolled and searched.
+pub struct LogWindow {
+    size: Size,
+    /// Number of lines hidden below the view, 0 shows the newest messages.
+    scroll: usize,
+    query: TextInput,
+    typing: bool,
+    /// Index of message found by the last search.
+    found: Option<usize>,
+}
+
+impl LogWindow {
+    pub fn new(size: Size) -> Self {
+        Self {
+            size,
+            scroll: 0,
+            query: TextInput::new(),
+            typing: false,
+            found: None,
+        }
+    }
+
+    pub fn resize(&mut self, size: Size) {
+        self.size = size;
+    }
+
+    /// Scrolls log or edits search query. Returns true when window should be closed.
+    pub fn handle_input(&mut self, engine: &ConsoleEngine, log: &MessageLog) -> bool {
+        let count = log.messages().len();
+
+        if self.typing {
+            if engine.is_key_pressed(KeyCode::Esc) {
+                self.typing = false;
+                self.query.clear();
+                self.found = None;
+            } else if self.query.handle_input(engine) {
+                self.typing = false;
+                self.show_found(log, log.find_older(self.query.text(), count));
+            }
+            return false;
+        }
+
+        let rows = self.rows();
+        if engine.is_key_pressed(KeyCode::Esc) || engine.is_key_pressed(KeyCode::Char('l')) {
+            return true;
+        } else if engine.is_key_pressed(KeyCode::Char('/')) {
+            self.typing = true;
+            self.query.clear();
+        } else if engine.is_key_pressed(KeyCode::Char('n')) {
+            let before = self.found.unwrap_or(count);
+            self.show_found(log, log.find_older(self.query.text(), before));
+        } else if engine.is_key_pressed_with_modifier(KeyCode::Char('N'), KeyModifiers::SHIFT) {
+            if let Some(after) = self.found {
+                self.show_found(log, log.find_newer(self.query.text(), after));
+            }
+        } else if engine.is_key_pressed(KeyCode::Up) {
+            self.scroll += 1;
+        } else if engine.is_key_pressed(KeyCode::Down) {
+            self.scroll = self.scroll.saturating_sub(1);
+        } else if engine.is_key_pressed(KeyCode::PageUp) {
+            self.scroll += rows;
+        } else if engine.is_key_pressed(KeyCode::PageDown) {
+            self.scroll = self.scroll.saturating_sub(rows);
+        } else if engine.is_key_pressed(KeyCode::End) {
+            self.scroll = 0;
+        }
+
+        let max_scroll = self.lines(log).len().saturating_sub(rows);
+        self.scroll = self.scroll.min(max_scroll);
+        false
+    }
+
+    pub fn render(&self, border: &BorderSet, log: &MessageLog) -> Screen {
+        let mut screen = Screen::new(self.size.width, self.size.height);
+        let area = Rect::of_screen(&screen);
+        widget::frame(&mut screen, area, border, "Message log");
+
+        let lines = self.lines(log);
+        let rows = self.rows();
+        let end = lines.len() - self.scroll.min(lines.len());
+        let start = end.saturating_sub(rows);
+        for (y, (index, line)) in lines[start..end].iter().enumerate() {
+            let message = &log.messages()[*index];
+            let bg = if log.matches(*index, self.query.text()) {
+                Color::DarkBlue
+            } else {
+                Color::Reset
+            };
+            let fg = category_color(message.category);
+            screen.print_fbg(2, y as i32 + 1, line, fg, bg);
+        }
+
+        let (rest, help) = area.shrink(2, 1).split_bottom(1);
+        let (_, search) = rest.split_bottom(1);
+        if self.typing || !self.query.text().is_empty() {
+            let prompt = if !self.typing && self.found.is_none() {
+                "Not found: "
+            } else {
+                "Search: "
+            };
+            self.query.render(&mut screen, search, prompt, self.typing);
+        }
+        widget::label(&mut screen, help, HELP, Color::White, widget::Align::Center);
+
+        screen
+    }
+
+    /// Rows between frame and search line.
+    fn rows(&self) -> usize {
+        self.size.height as usize - 4
+    }
+
+    /// Wrapped lines of all messages with indexes of messages they belong to.
+    fn lines(&self, log: &MessageLog) -> Vec<(usize, String)> {
+        let width = self.size.width as usize - 4;
+        log.messages()
+            .iter()
+            .enumerate()
+            .flat_map(|(index, message)| {
+                super::wrap_text(&message.text, width)
+                    .into_iter()
+                    .map(move |line| (index, line))
+            })
+            .collect()
+    }
+
+    /// Scrolls log so found message is in the bottom row.
+    fn show_found(&mut self, log: &MessageLog, found: Option<usize>) {
+        let index = match found {
+            Some(index) => index,
+            None => {
+                self.found = None;
+                return;
+            }
+        };
+
+        let lines = self.lines(log);
+        let end = lines
+            .iter()
+            .rposition(|(line_index, _)| *line_index == index)
+            .map_or(lines.len(), |position| position + 1);
+        self.scroll = lines.len() - end;
+        self.found = Some(index);
+    }
+}
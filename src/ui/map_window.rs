@@ -0,0 +1,278 @@
+use super::{
+    widget::{self, Align, Rect, TextInput},
+    Size,
+};
+use crate::{map::Map, tiles::BorderSet, travel::Landmark, vector::Vec2};
+use console_engine::{
+    pixel::{self, Pixel},
+    screen::Screen,
+    Color, ConsoleEngine, KeyCode,
+};
+use std::collections::{HashMap, HashSet};
+
+const HELP: &str = "Arrows move  n note  d delete note  Esc close";
+/// Tiles seen from visited tile in every direction.
+const SIGHT_RADIUS: i32 = 1;
+
+/// Things marked on map overview. Later kinds are drawn over earlier ones, when they share
+/// one cell of scaled map.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Marker {
+    Exit,
+    Chest,
+    Npc,
+    Note,
+    Player,
+}
+
+impl Marker {
+    fn pixel(self) -> Pixel {
+        let (glyph, color) = match self {
+            Marker::Exit => ('>', Color::Cyan),
+            Marker::Chest => ('$', Color::Yellow),
+            Marker::Npc => ('&', Color::Green),
+            Marker::Note => ('!', Color::Magenta),
+            Marker::Player => ('@', Color::White),
+        };
+        pixel::pxl_fbg(glyph, color, Color::Black)
+    }
+}
+
+/// Tiles player has seen: visited ones and those around them.
+pub fn explored_tiles(map: &Map, visited: impl Iterator<Item = Vec2>) -> HashSet<Vec2> {
+    let mut explored = HashSet::new();
+    for position in visited {
+        for dy in -SIGHT_RADIUS..=SIGHT_RADIUS {
+            for dx in -SIGHT_RADIUS..=SIGHT_RADIUS {
+                let seen = position + Vec2::new(dx, dy);
+                if map.contains(seen) {
+                    explored.insert(seen);
+                }
+            }
+        }
+    }
+    explored
+}
+
+/// Part of screen showing the whole map, with `scale` x `scale` tiles in every cell.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Projection {
+    area: Rect,
+    scale: i32,
+}
+
+impl Projection {
+    /// Scales map down, so it fits in area, and centers it there.
+    fn new(map_width: usize, map_height: usize, area: Rect) -> Self {
+        let scale = |tiles: usize, cells: u32| tiles.div_ceil(cells.max(1) as usize).max(1);
+        let scale = scale(map_width, area.width).max(scale(map_height, area.height));
+        let area = area.centered(
+            map_width.div_ceil(scale) as u32,
+            map_height.div_ceil(scale) as u32,
+        );
+        Self {
+            area,
+            scale: scale as i32,
+        }
+    }
+
+    /// Screen position of cell showing tile.
+    fn cell(&self, position: Vec2) -> Vec2 {
+        Vec2::new(
+            self.area.x + position.x / self.scale,
+            self.area.y + position.y / self.scale,
+        )
+    }
+}
+
+/// Draws explored part of map scaled down to fit in area. Cell shows the most important
+/// marker in its block of tiles, otherwise its first explored wall or other tile.
+fn draw_overview(
+    screen: &mut Screen,
+    projection: Projection,
+    map: &Map,
+    explored: &HashSet<Vec2>,
+    markers: &[(Vec2, Marker)],
+) {
+    // Walls show shape of explored area best, so they are preferred.
+    let rank = |position: Vec2| {
+        let passable = map.tile_at(position).is_some_and(|tile| tile.is_passable());
+        (passable, position.to_index(map.width()))
+    };
+    let mut cells: HashMap<Vec2, Vec2> = HashMap::new();
+    for position in explored.iter() {
+        let shown = cells.entry(projection.cell(*position)).or_insert(*position);
+        if rank(*position) < rank(*shown) {
+            *shown = *position;
+        }
+    }
+    for (cell, position) in cells {
+        if let Ok(pixel) = map.screen().get_pxl(position.x, position.y) {
+            screen.set_pxl(cell.x, cell.y, pixel);
+        }
+    }
+
+    let mut shown: HashMap<Vec2, Marker> = HashMap::new();
+    for (position, marker) in markers.iter() {
+        let cell = shown.entry(projection.cell(*position)).or_insert(*marker);
+        *cell = (*cell).max(*marker);
+    }
+    for (cell, marker) in shown {
+        screen.set_pxl(cell.x, cell.y, marker.pixel());
+    }
+}
+
+/// Small framed overview of the whole map.
+pub fn minimap(
+    size: Size,
+    border: &BorderSet,
+    map: &Map,
+    explored: &HashSet<Vec2>,
+    markers: &[(Vec2, Marker)],
+) -> Screen {
+    let mut screen = Screen::new(size.width, size.height);
+    let area = Rect::of_screen(&screen);
+    widget::frame(&mut screen, area, border, "Map");
+    let projection = Projection::new(map.width(), map.height(), area.inner());
+    draw_overview(&mut screen, projection, map, explored, markers);
+    screen
+}
+
+/// Full screen overview of explored map, where player can leave notes on tiles.
+pub struct MapWindow {
+    size: Size,
+    cursor: Vec2,
+    note: TextInput,
+    typing: bool,
+}
+
+impl MapWindow {
+    pub fn new(size: Size, cursor: Vec2) -> Self {
+        Self {
+            size,
+            cursor,
+            note: TextInput::new(),
+            typing: false,
+        }
+    }
+
+    pub fn resize(&mut self, size: Size) {
+        self.size = size;
+    }
+
+    /// Moves cursor, adds and removes notes. Returns true when window should be closed.
+    pub fn handle_input(
+        &mut self,
+        engine: &ConsoleEngine,
+        map: &Map,
+        notes: &mut Vec<Landmark>,
+    ) -> bool {
+        if self.typing {
+            if engine.is_key_pressed(KeyCode::Esc) {
+                self.typing = false;
+            } else if self.note.handle_input(engine) {
+                self.typing = false;
+                let text = self.note.text().trim().to_string();
+                if !text.is_empty() {
+                    notes.retain(|note| note.position != self.cursor);
+                    notes.push(Landmark::new(text, self.cursor));
+                }
+            }
+            return false;
+        }
+
+        if engine.is_key_pressed(KeyCode::Esc) || engine.is_key_pressed(KeyCode::Char('o')) {
+            return true;
+        } else if engine.is_key_pressed(KeyCode::Char('n')) {
+            self.typing = true;
+            self.note.clear();
+        } else if engine.is_key_pressed(KeyCode::Char('d')) {
+            notes.retain(|note| note.position != self.cursor);
+        }
+
+        // Cursor moves by one cell of scaled map.
+        let scale = self.projection(map).scale;
+        let direction = if engine.is_key_pressed(KeyCode::Up) {
+            Vec2::UP
+        } else if engine.is_key_pressed(KeyCode::Down) {
+            Vec2::DOWN
+        } else if engine.is_key_pressed(KeyCode::Left) {
+            Vec2::LEFT
+        } else if engine.is_key_pressed(KeyCode::Right) {
+            Vec2::RIGHT
+        } else {
+            Vec2::ZERO
+        };
+        let cursor = self.cursor + Vec2::new(direction.x * scale, direction.y * scale);
+        if map.contains(cursor) {
+            self.cursor = cursor;
+        }
+
+        false
+    }
+
+    pub fn render(
+        &self,
+        border: &BorderSet,
+        map: &Map,
+        explored: &HashSet<Vec2>,
+        markers: &[(Vec2, Marker)],
+        notes: &[Landmark],
+    ) -> Screen {
+        let mut screen = Screen::new(self.size.width, self.size.height);
+        let area = Rect::of_screen(&screen);
+        widget::frame(&mut screen, area, border, "Map");
+
+        let projection = self.projection(map);
+        draw_overview(&mut screen, projection, map, explored, markers);
+
+        let cell = projection.cell(self.cursor);
+        if let Ok(mut pixel) = screen.get_pxl(cell.x, cell.y) {
+            pixel.bg = Color::DarkBlue;
+            screen.set_pxl(cell.x, cell.y, pixel);
+        }
+
+        let (rest, help) = area.shrink(2, 1).split_bottom(1);
+        let (_, info) = rest.split_bottom(1);
+        if self.typing {
+            self.note.render(&mut screen, info, "Note: ", true);
+        } else {
+            let tile = match map.tile_at(self.cursor) {
+                Some(tile) if explored.contains(&self.cursor) => tile.key(),
+                _ => "unexplored",
+            };
+            let mut text = format!("({}, {}) {}", self.cursor.x, self.cursor.y, tile);
+            if let Some(note) = notes.iter().find(|note| note.position == self.cursor) {
+                text.push_str(&format!(": {}", note.name));
+            }
+            widget::label(&mut screen, info, &text, Color::White, Align::Left);
+        }
+        widget::label(&mut screen, help, HELP, Color::White, Align::Center);
+
+        screen
+    }
+
+    /// Map is drawn between frame and info line.
+    fn projection(&self, map: &Map) -> Projection {
+        let area = Rect::new(1, 1, self.size.width - 2, self.size.height - 4);
+        Projection::new(map.width(), map.height(), area)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn large_maps_are_scaled_down() {
+        let area = Rect::new(1, 1, 78, 20);
+        let small = Projection::new(60, 15, area);
+        assert_eq!(small.scale, 1);
+        assert_eq!(small.area, Rect::new(10, 3, 60, 15));
+
+        let large = Projection::new(200, 60, area);
+        assert_eq!(large.scale, 3);
+        assert_eq!(large.area, Rect::new(6, 1, 67, 20));
+        assert_eq!(large.cell(Vec2::new(199, 59)), Vec2::new(72, 20));
+    }
+}
@@ -1,8 +1,26 @@
+use super::widget::{self, Rect};
 use crate::player::Player;
-use console_engine::screen::Screen;
+use console_engine::{screen::Screen, Color};
 
 const LABEL_COL: i32 = 0;
 const VALUE_COL: i32 = 6;
+/// Bars start after short labels like "HP".
+const BAR_COL: i32 = 3;
+const BAR_WIDTH: u32 = 8;
+
+/// Color of health bar, which changes when health gets low.
+pub fn health_color(current: u8, max: u8) -> Color {
+    let percent = if max == 0 {
+        0
+    } else {
+        current as u32 * 100 / max as u32
+    };
+    match percent {
+        51.. => Color::Green,
+        26..=50 => Color::Yellow,
+        _ => Color::Red,
+    }
+}
 
 pub struct SimplePlayerScr<'a> {
     player: &'a Player,
@@ -45,32 +63,24 @@ impl<'a> SimplePlayerScr<'a> {
             &format!("{}/{}", p.remaining_moves(), p.max_moves()),
         );
 
-        // Experience
-        row += 1;
-        s.print(LABEL_COL, row, "XP:");
-        s.print(
-            VALUE_COL,
-            row,
-            &format!("{}/{}", p.current_xp(), p.next_level_xp()),
-        );
-
-        // Health
-        row += 1;
-        s.print(LABEL_COL, row, "Hlth:");
-        s.print(
-            VALUE_COL,
-            row,
-            &format!("{}/{}", p.current_health(), p.max_health()),
-        );
-
-        // Mana
-        row += 1;
-        s.print(LABEL_COL, row, "Mana:");
-        s.print(
-            VALUE_COL,
-            row,
-            &format!("{}/{}", p.current_mana(), p.max_mana()),
-        );
+        // Health, mana and experience bars
+        let bars = [
+            (
+                "HP",
+                p.current_health(),
+                p.max_health(),
+                health_color(p.current_health(), p.max_health()),
+            ),
+            ("MP", p.current_mana(), p.max_mana(), Color::Blue),
+            ("XP", p.current_xp(), p.next_level_xp(), Color::Magenta),
+        ];
+        for (label, current, max, color) in bars.iter() {
+            row += 1;
+            s.print(LABEL_COL, row, label);
+            let bar = Rect::new(BAR_COL, row, BAR_WIDTH, 1);
+            widget::progress_bar(s, bar, *current as u32, *max as u32, *color);
+            s.print(bar.right() + 1, row, &format!("{}/{}", current, max));
+        }
 
         // Attack
         row += 1;
@@ -78,9 +88,8 @@ impl<'a> SimplePlayerScr<'a> {
         s.print(VALUE_COL, row, &format!("{}", p.attack()));
 
         // Defense
-        row += 1;
-        s.print(LABEL_COL, row, "Def:");
-        s.print(VALUE_COL, row, &format!("{}", p.defense()));
+        s.print(VALUE_COL + 5, row, "Def:");
+        s.print(VALUE_COL + 10, row, &format!("{}", p.defense()));
 
         // Strenght
         row += 2;
@@ -0,0 +1,104 @@
+use super::{
+    widget::{self, Align, Rect},
+    Size,
+};
+use crate::{item::Item, tiles::BorderSet};
+use console_engine::{pixel, screen::Screen, Color, ConsoleEngine, KeyCode};
+
+const HELP: &str = "Left/Right side  Enter buy/sell  Esc close";
+/// Columns taken by item prices.
+const PRICE_WIDTH: u32 = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TradeSide {
+    Merchant,
+    Player,
+}
+
+/// Window with merchant stock on the left and player inventory on the right. Items are
+/// listed with prices for which they are bought or sold.
+pub struct TradeWindow {
+    size: Size,
+    side: TradeSide,
+    selected: usize,
+}
+
+impl TradeWindow {
+    pub fn new(size: Size) -> Self {
+        Self {
+            size,
+            side: TradeSide::Merchant,
+            selected: 0,
+        }
+    }
+
+    /// Switches between lists and moves selection in the active one. `count` is number of
+    /// items on the active side, counted after handling the switch.
+    pub fn handle_input(&mut self, engine: &ConsoleEngine, count: impl Fn(TradeSide) -> usize) {
+        if engine.is_key_pressed(KeyCode::Left) {
+            self.side = TradeSide::Merchant;
+        } else if engine.is_key_pressed(KeyCode::Right) {
+            self.side = TradeSide::Player;
+        }
+
+        widget::move_selection(engine, &mut self.selected, count(self.side));
+    }
+
+    pub fn render(
+        &self,
+        border: &BorderSet,
+        stock: &[Item],
+        inventory: &[Item],
+        gold: u32,
+    ) -> Screen {
+        let mut screen = Screen::new(self.size.width, self.size.height);
+        let area = Rect::of_screen(&screen);
+        let title = format!("Trade, your gold: {}", gold);
+        widget::frame(&mut screen, area, border, &title);
+
+        let (lists, help) = area.inner().split_bottom(2);
+        let (left, right) = lists.split_left(lists.width / 2);
+        screen.v_line(
+            right.x,
+            lists.y,
+            lists.bottom() - 1,
+            pixel::pxl(border.vertical),
+        );
+
+        for (area, side, items) in [
+            (left, TradeSide::Merchant, stock),
+            (right.split_left(1).1, TradeSide::Player, inventory),
+        ] {
+            let (heading, price) = match side {
+                TradeSide::Merchant => ("Buy", Item::value as fn(&Item) -> u32),
+                TradeSide::Player => ("Sell", Item::sell_value as fn(&Item) -> u32),
+            };
+            let rows: Vec<Vec<String>> = items
+                .iter()
+                .map(|item| vec![item.to_string(), format!("{}g", price(item))])
+                .collect();
+            let selected = if side == self.side {
+                Some(self.selected)
+            } else {
+                None
+            };
+
+            let area = area.shrink(1, 0);
+            let columns = [(heading, area.width - PRICE_WIDTH), ("", PRICE_WIDTH)];
+            widget::table(&mut screen, area, &columns, &rows, selected);
+        }
+
+        let (_, help) = help.split_top(1);
+        widget::label(&mut screen, help, HELP, Color::White, Align::Center);
+
+        screen
+    }
+
+    pub fn side(&self) -> TradeSide {
+        self.side
+    }
+
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+}
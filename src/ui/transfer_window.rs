@@ -0,0 +1,58 @@
+use super::{
+    widget::{self, Align, Rect},
+    Size,
+};
+use crate::{item::Item, tiles::BorderSet};
+use console_engine::{screen::Screen, Color, ConsoleEngine};
+
+const HELP: &str = "Enter take  a take all  Esc close";
+
+/// Window listing items of opened container, from which player picks items to take.
+pub struct TransferWindow {
+    title: String,
+    size: Size,
+    selected: usize,
+    help: &'static str,
+}
+
+impl TransferWindow {
+    pub fn new(size: Size, title: String) -> Self {
+        Self {
+            title,
+            size,
+            selected: 0,
+            help: HELP,
+        }
+    }
+
+    /// Replaces list of keys shown at the bottom, for windows used in other way than
+    /// taking items.
+    pub fn with_help(self, help: &'static str) -> Self {
+        Self { help, ..self }
+    }
+
+    /// Moves selection up and down the list of `count` items.
+    pub fn handle_input(&mut self, engine: &ConsoleEngine, count: usize) {
+        widget::move_selection(engine, &mut self.selected, count);
+    }
+
+    pub fn render(&self, border: &BorderSet, items: &[Item]) -> Screen {
+        let mut screen = Screen::new(self.size.width, self.size.height);
+        let area = Rect::of_screen(&screen);
+        widget::frame(&mut screen, area, border, &self.title);
+
+        let (list, help) = area.shrink(2, 1).split_bottom(1);
+        if items.is_empty() {
+            widget::label(&mut screen, list, "(empty)", Color::White, Align::Left);
+        }
+        let items: Vec<String> = items.iter().map(Item::to_string).collect();
+        widget::list(&mut screen, list, &items, Some(self.selected));
+        widget::label(&mut screen, help, self.help, Color::White, Align::Center);
+
+        screen
+    }
+
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+}
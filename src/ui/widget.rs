@@ -0,0 +1,318 @@
+//! Small building blocks of windows and panels. Widgets are drawn into given area of
+//! screen, so windows only decide where they go.
+
+use super::Size;
+use crate::tiles::BorderSet;
+use console_engine::{pixel, screen::Screen, Color, ConsoleEngine, KeyCode, KeyModifiers};
+use unicode_width::UnicodeWidthStr;
+
+/// Characters which can be typed into text inputs.
+const INPUT_CHARS: &str = "abcdefghijklmnopqrstuvwxyz0123456789 _-.,\"";
+
+/// Area of screen, which can be split between widgets.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rect {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Rect {
+    pub fn new(x: i32, y: i32, width: u32, height: u32) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// Area covering the whole screen.
+    pub fn of_screen(screen: &Screen) -> Self {
+        Self::new(0, 0, screen.get_width(), screen.get_height())
+    }
+
+    pub fn size(&self) -> Size {
+        Size::new(self.width, self.height)
+    }
+
+    /// Area inside of the frame drawn around this one.
+    pub fn inner(&self) -> Self {
+        self.shrink(1, 1)
+    }
+
+    /// Area with `dx` columns and `dy` rows removed on every side.
+    pub fn shrink(&self, dx: u32, dy: u32) -> Self {
+        Self::new(
+            self.x + dx as i32,
+            self.y + dy as i32,
+            self.width.saturating_sub(2 * dx),
+            self.height.saturating_sub(2 * dy),
+        )
+    }
+
+    /// Splits area into left part `width` columns wide and the rest.
+    pub fn split_left(&self, width: u32) -> (Self, Self) {
+        let width = width.min(self.width);
+        (
+            Self::new(self.x, self.y, width, self.height),
+            Self::new(
+                self.x + width as i32,
+                self.y,
+                self.width - width,
+                self.height,
+            ),
+        )
+    }
+
+    /// Splits area into top part `height` rows high and the rest.
+    pub fn split_top(&self, height: u32) -> (Self, Self) {
+        let height = height.min(self.height);
+        (
+            Self::new(self.x, self.y, self.width, height),
+            Self::new(
+                self.x,
+                self.y + height as i32,
+                self.width,
+                self.height - height,
+            ),
+        )
+    }
+
+    /// Splits area into the rest and bottom part `height` rows high.
+    pub fn split_bottom(&self, height: u32) -> (Self, Self) {
+        self.split_top(self.height.saturating_sub(height))
+    }
+
+    /// Area of given size in the middle of this one.
+    pub fn centered(&self, width: u32, height: u32) -> Self {
+        let width = width.min(self.width);
+        let height = height.min(self.height);
+        Self::new(
+            self.x + (self.width - width) as i32 / 2,
+            self.y + (self.height - height) as i32 / 2,
+            width,
+            height,
+        )
+    }
+
+    pub fn right(&self) -> i32 {
+        self.x + self.width as i32
+    }
+
+    pub fn bottom(&self) -> i32 {
+        self.y + self.height as i32
+    }
+}
+
+/// Draws frame at the edges of area with title in the middle of top line.
+pub fn frame(screen: &mut Screen, area: Rect, border: &BorderSet, title: &str) {
+    if area.width < 2 || area.height < 2 {
+        return;
+    }
+
+    let right = area.right() - 1;
+    let bottom = area.bottom() - 1;
+    screen.h_line(area.x, area.y, right, pixel::pxl(border.horizontal));
+    screen.h_line(area.x, bottom, right, pixel::pxl(border.horizontal));
+    screen.v_line(area.x, area.y, bottom, pixel::pxl(border.vertical));
+    screen.v_line(right, area.y, bottom, pixel::pxl(border.vertical));
+    screen.set_pxl(area.x, area.y, pixel::pxl(border.top_left));
+    screen.set_pxl(right, area.y, pixel::pxl(border.top_right));
+    screen.set_pxl(area.x, bottom, pixel::pxl(border.bottom_left));
+    screen.set_pxl(right, bottom, pixel::pxl(border.bottom_right));
+
+    if !title.is_empty() {
+        let top = Rect::new(area.x + 1, area.y, area.width - 2, 1);
+        label(screen, top, title, Color::White, Align::Center);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Align {
+    Left,
+    Center,
+    Right,
+}
+
+/// Prints text in the first row of area. Text wider than area is cut.
+pub fn label(screen: &mut Screen, area: Rect, text: &str, fg: Color, align: Align) {
+    let text = fit(text, area.width as usize);
+    let free = area.width as i32 - text.width() as i32;
+    let x = match align {
+        Align::Left => area.x,
+        Align::Center => area.x + free / 2,
+        Align::Right => area.x + free,
+    };
+    screen.print_fbg(x, area.y, &text, fg, Color::Reset);
+}
+
+/// Lists items one per row. List is scrolled, so selected item is always visible.
+pub fn list(screen: &mut Screen, area: Rect, items: &[String], selected: Option<usize>) {
+    let rows = area.height as usize;
+    let first = (selected.unwrap_or(0) + 1).saturating_sub(rows);
+    for (row, (idx, item)) in items.iter().enumerate().skip(first).take(rows).enumerate() {
+        let background = if selected == Some(idx) {
+            Color::DarkBlue
+        } else {
+            Color::Black
+        };
+        let text = fit(item, area.width as usize);
+        screen.print_fbg(area.x, area.y + row as i32, &text, Color::White, background);
+    }
+}
+
+/// Moves list selection with Up and Down keys and keeps it within `count` items.
+pub fn move_selection(engine: &ConsoleEngine, selected: &mut usize, count: usize) {
+    if engine.is_key_pressed(KeyCode::Up) && *selected > 0 {
+        *selected -= 1;
+    } else if engine.is_key_pressed(KeyCode::Down) && *selected + 1 < count {
+        *selected += 1;
+    }
+
+    // Item removed from the end of the list moves selection to the new last item.
+    *selected = (*selected).min(count.saturating_sub(1));
+}
+
+/// Draws bar filled proportionally to `value` out of `max` in the first row of area.
+pub fn progress_bar(screen: &mut Screen, area: Rect, value: u32, max: u32, color: Color) {
+    let width = area.width as usize;
+    let filled = if max == 0 {
+        0
+    } else {
+        (value.min(max) as usize * width + max as usize / 2) / max as usize
+    };
+    screen.print_fbg(area.x, area.y, &"█".repeat(filled), color, Color::Reset);
+    screen.print_fbg(
+        area.x + filled as i32,
+        area.y,
+        &"░".repeat(width - filled),
+        Color::DarkGrey,
+        Color::Reset,
+    );
+}
+
+/// Rows of cells in columns of given widths, with headings in the first row.
+pub fn table(
+    screen: &mut Screen,
+    area: Rect,
+    columns: &[(&str, u32)],
+    rows: &[Vec<String>],
+    selected: Option<usize>,
+) {
+    let line = |cells: Vec<&str>| {
+        let mut line = String::new();
+        for (cell, (_, width)) in cells.iter().zip(columns.iter()) {
+            let cell = fit(cell, *width as usize);
+            let padding = (*width as usize).saturating_sub(cell.width());
+            line.push_str(&cell);
+            line.push_str(&" ".repeat(padding));
+        }
+        line
+    };
+
+    let headings = line(columns.iter().map(|(heading, _)| *heading).collect());
+    label(screen, area, &headings, Color::Yellow, Align::Left);
+
+    let (_, body) = area.split_top(1);
+    let lines: Vec<String> = rows
+        .iter()
+        .map(|row| line(row.iter().map(String::as_str).collect()))
+        .collect();
+    list(screen, body, &lines, selected);
+}
+
+/// Single line text field.
+#[derive(Debug, Default)]
+pub struct TextInput {
+    text: String,
+}
+
+impl TextInput {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn clear(&mut self) {
+        self.text.clear();
+    }
+
+    /// Appends typed characters and removes last one on Backspace. Returns true when Enter
+    /// is pressed.
+    pub fn handle_input(&mut self, engine: &ConsoleEngine) -> bool {
+        if engine.is_key_pressed(KeyCode::Enter) {
+            return true;
+        }
+        if engine.is_key_pressed(KeyCode::Backspace) {
+            self.text.pop();
+        }
+
+        for ch in INPUT_CHARS.chars() {
+            if engine.is_key_pressed(KeyCode::Char(ch)) {
+                self.text.push(ch);
+            } else if ch.is_ascii_lowercase() {
+                let upper = ch.to_ascii_uppercase();
+                if engine.is_key_pressed_with_modifier(KeyCode::Char(upper), KeyModifiers::SHIFT) {
+                    self.text.push(upper);
+                }
+            }
+        }
+
+        false
+    }
+
+    /// Draws text after prompt with cursor at its end. Text wider than area is scrolled,
+    /// so its end is visible.
+    pub fn render(&self, screen: &mut Screen, area: Rect, prompt: &str, focused: bool) {
+        let cursor = if focused { "_" } else { "" };
+        let width = (area.width as usize).saturating_sub(prompt.width() + cursor.len());
+        let skip = self.text.chars().count().saturating_sub(width);
+        let visible: String = self.text.chars().skip(skip).collect();
+        let text = format!("{}{}{}", prompt, visible, cursor);
+        label(screen, area, &text, Color::White, Align::Left);
+    }
+}
+
+/// Cuts text to fit in `width` columns.
+fn fit(text: &str, width: usize) -> String {
+    if text.width() <= width {
+        return text.to_string();
+    }
+
+    let mut fitted = String::new();
+    for ch in text.chars() {
+        fitted.push(ch);
+        if fitted.width() > width {
+            fitted.pop();
+            break;
+        }
+    }
+    fitted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rects_are_split() {
+        let area = Rect::new(0, 0, 80, 40);
+        let (left, right) = area.split_left(60);
+        assert_eq!(left, Rect::new(0, 0, 60, 40));
+        assert_eq!(right, Rect::new(60, 0, 20, 40));
+
+        let (top, bottom) = right.split_bottom(5);
+        assert_eq!(top, Rect::new(60, 0, 20, 35));
+        assert_eq!(bottom, Rect::new(60, 35, 20, 5));
+
+        assert_eq!(left.centered(20, 10), Rect::new(20, 15, 20, 10));
+        assert_eq!(left.centered(100, 10).width, 60);
+        assert_eq!(bottom.inner(), Rect::new(61, 36, 18, 3));
+        assert_eq!(fit("Helmet h(1)", 6), "Helmet");
+    }
+}